@@ -1,9 +1,4 @@
-#![allow(unused)]
-
 use itertools::sorted;
-use proptest::{prelude::*, sample::select};
-use rand::seq::SliceRandom;
-use rand::thread_rng;
 use rustc_hash::FxHashSet as HashSet;
 use smallvec::{smallvec, SmallVec};
 use std::cmp::Ordering;
@@ -11,12 +6,22 @@ use std::cmp::Ordering;
 use Ordering::*;
 
 use crate::{
-  board::{covers_board, Board},
-  omino::{
-    enumerate_polyominos, normalize_omino, offset_in_dir, sum_points, translate_omino, Dir,
-    FreePoint, FreePointList, PointList,
-  },
+  board::{Board, MAX_SIZE},
+  omino::{normalize_omino, offset_in_dir, translate_omino, Dir, FreePoint, FreePointList},
+};
+//for the property tests at the end
+#[cfg(test)]
+use crate::{
+  board::has_arrangement_board,
+  omino::{enumerate_polyominos, slow_omino_enum, PointList},
+  placements::has_arrangement_table,
 };
+#[cfg(test)]
+use proptest::{prelude::*, sample::select};
+#[cfg(test)]
+use rand::{seq::SliceRandom, thread_rng};
+#[cfg(test)]
+use std::ops::RangeInclusive;
 use Dir::*;
 
 /*
@@ -68,7 +73,7 @@ pub fn iter_perimeter_slow(fps: &FreePointList) -> Vec<Edge> {
       neighbors.push((neighbor, Edge(pt, dir)));
     }
   }
-  neighbors.sort_unstable_by_key(|&(neighbor, Edge(pt, dir))| neighbor);
+  neighbors.sort_unstable_by_key(|&(neighbor, Edge(_pt, _dir))| neighbor);
 
   let mut out = vec![];
   let mut fps_index = 0;
@@ -94,8 +99,13 @@ pub fn iter_perimeter_slow(fps: &FreePointList) -> Vec<Edge> {
 }
 
 pub fn iter_perimeter(fps: &FreePointList) -> Vec<Edge> {
-  let mut board = Board::empty();
-  board.add_always(&fps);
+  iter_perimeter_on::<MAX_SIZE>(fps)
+}
+
+pub fn iter_perimeter_on<const SIZE: usize>(fps: &FreePointList) -> Vec<Edge> {
+  //the board must be large enough for the omino, see Board::fits
+  let mut board = Board::<SIZE>::empty();
+  board.add_always(fps);
   let mut out = Vec::with_capacity((fps.len() * 2) + 2);
   for &pt in fps {
     let xs = pt.get_neighbors_with_directions();
@@ -151,11 +161,11 @@ pub fn rotate_180(FreePoint { x, y }: FreePoint) -> FreePoint {
 }
 
 pub fn mirror_x_axis(FreePoint { x, y }: FreePoint) -> FreePoint {
-  FreePoint { x: x, y: -y }
+  FreePoint { x, y: -y }
 }
 
 pub fn mirror_y_axis(FreePoint { x, y }: FreePoint) -> FreePoint {
-  FreePoint { x: -x, y: y }
+  FreePoint { x: -x, y }
 }
 
 pub fn rotational_equivalence(omino: &FreePointList, omino2: &FreePointList) -> bool {
@@ -183,13 +193,12 @@ pub fn rotational_deduplicate(ominos: &Vec<FreePointList>) -> Vec<FreePointList>
 }
 
 pub fn rotate_omino(omino: &FreePointList, rotate_amt: u8) -> FreePointList {
-  //invariant: rotate_amt is 0,1,2,3 and represents the number of cw turns
-  match rotate_amt {
+  //rotate_amt is the number of cw turns, so 4 turns are none
+  match rotate_amt % 4 {
     0 => omino.clone(),
     1 => omino.iter().map(|&p| rotate_cw(p)).collect(),
     2 => omino.iter().map(|&p| rotate_180(p)).collect(),
-    3 => omino.iter().map(|&p| rotate_ccw(p)).collect(),
-    _ => unreachable!("rotate amount not <4"),
+    _ => omino.iter().map(|&p| rotate_ccw(p)).collect(),
   }
 }
 
@@ -230,7 +239,7 @@ pub fn align_perim(
 ) -> FreePointList {
   let (rotated_omino, rotated_src_pt) = rotate_omino_edge(omino, src, target_dir);
   let translation = translation_of_a_to_b(rotated_src_pt, target_point);
-  return translate_omino(&rotated_omino, translation);
+  translate_omino(&rotated_omino, translation)
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Default)]
@@ -246,14 +255,14 @@ pub fn merge_pts_slow(pts: &FreePointList, mut new_pts: FreePointList) -> Option
    */
   let set: HashSet<_> = HashSet::from_iter(pts.iter());
   if new_pts.iter().any(|pt| set.contains(pt)) {
-    return None;
+    None
   } else {
     new_pts.extend(pts.clone());
-    return Some(new_pts);
+    Some(new_pts)
   }
 }
 
-pub fn merge_pts(pts: &FreePointList, mut new_pts: FreePointList) -> Option<FreePointList> {
+pub fn merge_pts(pts: &FreePointList, new_pts: FreePointList) -> Option<FreePointList> {
   /* invariants: pts and new_pts are sorted
   if pts and new_pts overlap, then return None.
   else, return a new sorted list of pts that is their union.
@@ -296,7 +305,7 @@ pub fn next_edge_to_cover(pts: &FreePointList) -> Option<Edge> {
   //returns one of [((0, 1), S), ((1, 0), W), ((0, -1), N), ((-1, 0), E)]
   let edges_to_cover =
     [N, E, S, W].map(|d| Edge(offset_in_dir(FreePoint { x: 0, y: 0 }, d), d.flip()));
-  edges_to_cover.iter().copied().filter(|Edge(pt, _d)| !pts.contains(pt)).next()
+  edges_to_cover.iter().copied().find(|Edge(pt, _d)| !pts.contains(pt))
 }
 
 pub fn translate_a_to_b(
@@ -312,7 +321,7 @@ pub fn translate_a_to_b(
 
 pub fn add_translation_children(
   omino: &FreePointList, //invariant: sorted
-  perimeter: &Vec<Edge>,
+  perimeter: &[Edge],
   stack: &mut Vec<ConfigurationTranslation>,
   ConfigurationTranslation { pts, translations }: ConfigurationTranslation,
 ) -> Option<SmallVec<[FreePoint; 4]>> {
@@ -321,15 +330,15 @@ pub fn add_translation_children(
 
   let Edge(pt_to_cover, dir_to_cover) = next_edge_to_cover(&pts).unwrap();
   // dbg!(&dir_to_cover, &pt_to_cover);
-  let mut possible_edges = perimeter.iter().filter(|&&(Edge(fp, d))| d == dir_to_cover);
-  let mut translated_ominos_and_translations =
-    possible_edges.map(|&(Edge(src_pt, _src_dir))| translate_a_to_b(&omino, src_pt, pt_to_cover));
+  let possible_edges = perimeter.iter().filter(|&&Edge(_fp, d)| d == dir_to_cover);
+  let translated_ominos_and_translations =
+    possible_edges.map(|&Edge(src_pt, _src_dir)| translate_a_to_b(omino, src_pt, pt_to_cover));
   for (translated_omino, translation) in translated_ominos_and_translations {
     // dbg!(&translated_omino, &translation);
     if let Some(merged_pts) = merge_pts(&pts, translated_omino) {
       let mut new_translations = translations.clone();
       new_translations.push(translation);
-      if let None = next_edge_to_cover(&merged_pts) {
+      if next_edge_to_cover(&merged_pts).is_none() {
         //we win, return that
         return Some(new_translations);
       } else {
@@ -356,11 +365,10 @@ pub fn find_arrangement_translation(omino: &FreePointList) -> Option<SmallVec<[F
    */
   let mut stack: Vec<ConfigurationTranslation> = vec![ConfigurationTranslation::default()];
   let perimeter = iter_perimeter(omino);
-  let sorted_omino: FreePointList = sorted(omino.iter()).map(|&x| x).collect();
+  let sorted_omino: FreePointList = sorted(omino.iter()).copied().collect();
   while let Some(config) = stack.pop() {
-    match add_translation_children(&sorted_omino, &perimeter, &mut stack, config) {
-      Some(ans) => return Some(ans),
-      None => (),
+    if let Some(ans) = add_translation_children(&sorted_omino, &perimeter, &mut stack, config) {
+      return Some(ans);
     }
   }
   None
@@ -387,12 +395,12 @@ pub fn add_tr_children(
 
   fn extract_possible_pairs<'a>(
     omino: &'a FreePointList,
-    perimeter: &'a Vec<Edge>,
+    perimeter: &'a [Edge],
     edge_to_cover: Edge,
   ) -> impl Iterator<Item = (FreePointList, (Edge, Edge))> + 'a {
     let Edge(pt_to_cover, dir_to_cover) = edge_to_cover;
-    perimeter.iter().filter_map(move |&(Edge(fp, d))| {
-      if (d == dir_to_cover) {
+    perimeter.iter().filter_map(move |&Edge(fp, d)| {
+      if d == dir_to_cover {
         Some((translate_a_to_b(omino, fp, pt_to_cover).0, (Edge(fp, d), edge_to_cover)))
       } else {
         None
@@ -400,14 +408,14 @@ pub fn add_tr_children(
     })
   }
 
-  let mut possible_ominos_and_edge_pairs =
+  let possible_ominos_and_edge_pairs =
     (0..=3).flat_map(|i| extract_possible_pairs(&ominos[i], &perimeters[i], edge_to_cover));
 
   for (moved_omino, edge_pair) in possible_ominos_and_edge_pairs {
     if let Some(merged_pts) = merge_pts(&pts, moved_omino) {
       let mut new_pairs = edge_pairs.clone();
       new_pairs.push(edge_pair);
-      if let None = next_edge_to_cover(&merged_pts) {
+      if next_edge_to_cover(&merged_pts).is_none() {
         //we win, return that
         return Some(edge_pairs);
       } else {
@@ -433,39 +441,58 @@ pub fn find_arrangement(omino: &FreePointList) -> Option<SmallVec<[(Edge, Edge);
    */
   let mut stack = vec![Configuration::default()];
   let mut rotated_ominos = [0, 1, 2, 3].map(|amt| rotate_omino(omino, amt));
-  for i in (0..=3) {
-    rotated_ominos[i].sort_unstable();
-    if has_corner_arrangement(&rotated_ominos[i]) {
+  for rotated_omino in rotated_ominos.iter_mut() {
+    rotated_omino.sort_unstable();
+    if has_corner_arrangement(rotated_omino) {
       return Some(smallvec![]);
     }
   }
   let perimeters = rotated_ominos.clone().map(|omino| iter_perimeter(&omino));
   while let Some(config) = stack.pop() {
-    match add_tr_children(&rotated_ominos, &perimeters, &mut stack, config) {
-      Some(ans) => return Some(ans),
-      None => (),
+    if let Some(ans) = add_tr_children(&rotated_ominos, &perimeters, &mut stack, config) {
+      return Some(ans);
     }
   }
   None
 }
 
+pub fn verify_arrangement(omino: &FreePointList, copies: &[FreePointList]) -> bool {
+  /* checks that copies surround the hole (0,0): every copy is a rotation and
+  translation of omino, no two copies overlap, none of them covers the hole, and
+  together they cover the 4 cells next to it */
+  let hole = FreePoint { x: 0, y: 0 };
+  let mut covered = HashSet::default();
+  for copy in copies {
+    if !rotational_equivalence(omino, copy) {
+      return false;
+    }
+    for &pt in copy {
+      if pt == hole || !covered.insert(pt) {
+        return false;
+      }
+    }
+  }
+  hole.get_neighbors().iter().all(|pt| covered.contains(pt))
+}
+
 pub fn has_rotated_corner_arrangement(omino: &FreePointList) -> bool {
   let mut rotated_ominos = [0, 1, 2, 3].map(|amt| rotate_omino(omino, amt));
-  for i in (0..=3) {
-    rotated_ominos[i].sort_unstable();
-    // dbg!(&rotated_ominos[i]);
-    if has_corner_arrangement(&rotated_ominos[i]) {
+  for rotated_omino in rotated_ominos.iter_mut() {
+    rotated_omino.sort_unstable();
+    // dbg!(&rotated_omino);
+    if has_corner_arrangement(rotated_omino) {
       return true;
     }
   }
-  return false;
+  false
 }
 
 pub fn has_corner_arrangement(omino: &[FreePoint]) -> bool {
-  let FreePoint { x: corner_x, y: corner_y } = omino[0];
-  !omino.iter().any(|FreePoint { x, y }| *y < corner_y)
+  let FreePoint { x: _corner_x, y: corner_y } = omino[0];
+  !omino.iter().any(|FreePoint { x: _, y }| *y < corner_y)
 }
 
+#[cfg(test)]
 pub mod test {
   use itertools::Itertools;
 
@@ -483,7 +510,7 @@ pub mod test {
   #[test]
   fn point_fiddling() {
     let pts = [(0, 0), (1, 3), (4, 4), (-3, 6), (3, -5), (20, 0)].map(|(x, y)| FreePoint { x, y });
-    pts.map(|p| point_assert(p));
+    pts.into_iter().for_each(point_assert);
   }
 
   #[test]
@@ -511,11 +538,23 @@ pub mod test {
     out.into_iter().collect()
   }
 
+  #[test]
+  fn enumeration_matches_slow_enum() {
+    for size in 1..=8 {
+      let mut enumerated = HashSet::default();
+      enumerate_polyominos(size, |pts| {
+        enumerated.insert(normalize_omino(pts.iter().map(|&pt| pt.into()).collect()));
+      });
+      let slow: HashSet<FreePointList> = slow_omino_enum(size).into_iter().collect();
+      assert_eq!(enumerated, slow, "size {}", size);
+    }
+  }
+
   #[test]
   fn unarrange_not_arrange() {
-    let mut un25 = unarrangeable25();
+    let un25 = unarrangeable25();
     dbg!(&un25);
-    let un25_grid: Grid = un25.clone().into();
+    let un25_grid = Grid::try_from(un25.clone()).unwrap();
     dbg!(&un25_grid);
     assert_eq!(un25.len(), 25);
     assert_eq!(find_arrangement(&un25), None);
@@ -523,19 +562,45 @@ pub mod test {
   }
 }
 
-fn omino_strategy(size: u8) -> impl Strategy<Value = FreePointList> {
-  let mut ominos: Vec<PointList> = vec![];
-  enumerate_polyominos(size, |pl| ominos.push(pl.into()));
+#[cfg(test)]
+fn omino_strategy(size: u8) -> BoxedStrategy<FreePointList> {
+  /* a fixed omino of the given size. Up to size 10 they are picked uniformly from the
+  enumeration, and larger ones, which take too long to enumerate, are grown a square
+  at a time by choices proptest can shrink */
   fn pl_to_fpl(pl: PointList) -> FreePointList {
     pl.into_iter().map(|x| x.into()).collect()
   }
-  return select(ominos).prop_map(pl_to_fpl);
+  if size <= 10 {
+    let mut ominos: Vec<PointList> = vec![];
+    enumerate_polyominos(size, |pl| ominos.push(pl.into()));
+    return select(ominos).prop_map(pl_to_fpl).boxed();
+  }
+  proptest::collection::vec(any::<usize>(), usize::from(size) - 1)
+    .prop_map(|choices| {
+      let mut omino: FreePointList = smallvec![FreePoint { x: 0, y: 0 }];
+      for choice in choices {
+        let mut candidates = FreePoint::get_all_neighbors(&omino);
+        candidates.retain(|pt| !omino.contains(pt));
+        candidates.sort();
+        omino.push(candidates[choice % candidates.len()]);
+      }
+      normalize_omino(omino)
+    })
+    .boxed()
 }
 
+#[cfg(test)]
+fn sized_omino_strategy(sizes: RangeInclusive<u8>) -> impl Strategy<Value = FreePointList> {
+  sizes.prop_flat_map(omino_strategy)
+}
+
+#[cfg(test)]
 fn point_strategy() -> impl Strategy<Value = FreePoint> {
-  (any::<i8>(), any::<i8>()).prop_map(|(x, y)| FreePoint { x, y })
+  //small enough that translating an omino or stepping off an edge can't overflow
+  (-64i8..64, -64i8..64).prop_map(|(x, y)| FreePoint { x, y })
 }
 
+#[cfg(test)]
 fn shuffle_omino(fps: &FreePointList) -> FreePointList {
   let mut out = fps.clone();
   let mut rng = thread_rng();
@@ -543,17 +608,11 @@ fn shuffle_omino(fps: &FreePointList) -> FreePointList {
   out
 }
 
+#[cfg(test)]
 proptest! {
-  #[test]
-  fn i64_abs_is_never_negative(a: i64) {
-      // This actually fails if a == i64::MIN, but randomly picking one
-      // specific value out of 2⁶⁴ is overwhelmingly unlikely.
-      assert!(a.abs() >= 0);
-  }
-
   #[test]
   fn small_ominos_are_arrangeable(omino in omino_strategy(10)) {
-    let fps = omino.into_iter().map(|x|x.into()).collect();
+    let fps = omino;
     match find_arrangement(&fps) {
       Some(_) => (),
       None => panic!("non arrangeable: {:?}", fps)
@@ -570,20 +629,70 @@ proptest! {
     prop_assert_eq!(original_perimeter, new_perimeter)
   }
 
+  #[test]
+  fn searches_agree(omino in sized_omino_strategy(4..=14)) {
+    //the board, table and stack searches answer the same question, and translations are rotations
+    let board = has_arrangement_board(&omino);
+    prop_assert_eq!(board, has_arrangement_table(&omino), "{:?}", omino);
+    prop_assert_eq!(board, find_arrangement(&omino).is_some(), "{:?}", omino);
+    if find_arrangement_translation(&omino).is_some() {
+      prop_assert!(board, "{:?}", omino);
+    }
+  }
+
+  #[test]
+  fn perimeter_matches_slow(omino in sized_omino_strategy(4..=14)) {
+    //the slow version needs the omino sorted
+    let omino: FreePointList = sorted(omino.iter()).copied().collect();
+    let mut perimeter = iter_perimeter(&omino);
+    perimeter.sort();
+    let mut slow_perimeter = iter_perimeter_slow(&omino);
+    slow_perimeter.sort();
+    prop_assert_eq!(perimeter, slow_perimeter);
+  }
+
   #[test]
   fn tranlation_preserves_sorted(omino in omino_strategy(10), point in point_strategy()) {
-    todo!()
+    let sorted_omino: FreePointList = sorted(omino.iter()).copied().collect();
+    prop_assert!(translate_omino(&sorted_omino, point).is_sorted());
   }
 
-  //#[test]
-  //fn test_merge_pts_correct(omino in omino_strategy(10)) {
-  //  //idk exactly how at 11pm
-  //  todo!()
-  //}
+  #[test]
+  fn test_merge_pts_correct(
+    omino in omino_strategy(6),
+    other in omino_strategy(6),
+    point in (-6i8..=6, -6i8..=6).prop_map(|(x, y)| FreePoint { x, y }),
+  ) {
+    //the fast merge needs both sorted, and agrees with the slow one as sets
+    let pts: FreePointList = sorted(omino.iter()).copied().collect();
+    let new_pts: FreePointList = sorted(translate_omino(&other, point).iter()).copied().collect();
+    let fast = merge_pts(&pts, new_pts.clone());
+    let slow = merge_pts_slow(&pts, new_pts).map(|merged| sorted(merged).collect::<FreePointList>());
+    prop_assert_eq!(fast, slow);
+  }
+
+  #[test]
+  fn normalize_is_idempotent(omino in omino_strategy(10), point in point_strategy()) {
+    let normalized = normalize_omino(omino.clone());
+    prop_assert_eq!(normalize_omino(normalized.clone()), normalized.clone());
+    prop_assert_eq!(normalize_omino(translate_omino(&omino, point)), normalized);
+  }
 
-  // #[test]
-  // fn i64_is_never_negative(a: i64) {
-  //     prop_assert!(a >= 0);
-  // }
+  #[test]
+  fn rotation_group_laws(omino in omino_strategy(10), a in 0u8..4, b in 0u8..4) {
+    //rotations compose by adding turns, and 4 turns are the identity
+    prop_assert_eq!(rotate_omino(&omino, 0), omino.clone());
+    prop_assert_eq!(rotate_omino(&rotate_omino(&omino, a), b), rotate_omino(&omino, a + b));
+    let inverse = rotate_omino(&rotate_omino(&omino, a), (4 - a) % 4);
+    prop_assert_eq!(inverse, omino.clone());
+  }
 
+  #[test]
+  fn edge_flip_is_involution(pt in point_strategy(), d in select(vec![N, E, S, W])) {
+    let edge = Edge(pt, d);
+    let flipped = edge.flip();
+    prop_assert_eq!(flipped.flip(), edge);
+    prop_assert_eq!(flipped.1, d.flip());
+    prop_assert_eq!(offset_in_dir(flipped.0, flipped.1), pt);
+  }
 }
@@ -0,0 +1,178 @@
+use std::{
+  fs::File,
+  io::{self, Write},
+  path::Path,
+  time::Instant,
+};
+
+use crate::{
+  assemble::{find_arrangement, find_arrangement_translation},
+  board::has_arrangement_board,
+  command::{failed, usage, CommandError},
+  omino::{enumerate_polyominos, FreePointList, MAX_ENUMERABLE_SIZE},
+  placements::has_arrangement_table,
+  transfer::count_fixed_polyominoes,
+};
+
+/*
+Timing the surround searches against each other. Each size gets a fixed corpus of
+ominos, evenly spaced through the enumeration order, so runs on different commits and
+machines time the same ominos. Every search runs over the whole corpus a few times
+and the fastest run counts, which keeps noise from other processes out of the figures.
+
+`find_arrangement_translation` only tries translations, so it answers a different
+question, but it is timed on the same corpus for comparison. The experimental
+searches are only timed when picked by name.
+ */
+
+pub type Search = fn(&FreePointList) -> bool;
+
+pub const SEARCHES: [(&str, Search); 3] = [
+  ("board", |omino| has_arrangement_board(omino)),
+  ("stack", |omino| find_arrangement(omino).is_some()),
+  ("translation", |omino| find_arrangement_translation(omino).is_some()),
+];
+
+pub const EXPERIMENTAL: [(&str, Search); 1] = [("table", |omino| has_arrangement_table(omino))];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchResult {
+  pub size: u8,
+  pub search: &'static str,
+  pub ominos: usize,
+  pub ns_per_omino: f64,
+}
+
+pub fn corpus(size: u8, max_ominos: usize) -> Vec<FreePointList> {
+  //at most max_ominos of the fixed ominos of the given size, evenly spaced
+  assert!(size <= MAX_ENUMERABLE_SIZE, "can enumerate up to size {}", MAX_ENUMERABLE_SIZE);
+  let count = count_fixed_polyominoes(size)[usize::from(size)];
+  let stride = count.div_ceil(max_ominos.max(1) as u128).max(1);
+  let mut out = vec![];
+  let mut index = 0;
+  enumerate_polyominos(size, |pts| {
+    if index % stride == 0 {
+      out.push(pts.iter().map(|&pt| pt.into()).collect());
+    }
+    index += 1;
+  });
+  out
+}
+
+pub fn bench_search(
+  size: u8,
+  (name, search): (&'static str, Search),
+  corpus: &[FreePointList],
+  runs: usize,
+) -> BenchResult {
+  let mut fastest = f64::INFINITY;
+  for _ in 0..runs.max(1) {
+    let start = Instant::now();
+    for omino in corpus {
+      std::hint::black_box(search(std::hint::black_box(omino)));
+    }
+    fastest = fastest.min(start.elapsed().as_nanos() as f64);
+  }
+  BenchResult {
+    size,
+    search: name,
+    ominos: corpus.len(),
+    ns_per_omino: fastest / corpus.len().max(1) as f64,
+  }
+}
+
+pub fn profile<T>(flamegraph: Option<&Path>, f: impl FnOnce() -> T) -> io::Result<T> {
+  /* runs f, sampling it with pprof and writing a flamegraph svg to the path if one is
+  given, in place of copying profile percentages by hand */
+  let Some(path) = flamegraph else { return Ok(f()) };
+  let guard = pprof::ProfilerGuardBuilder::default()
+    .frequency(1000)
+    .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+    .build()
+    .map_err(io::Error::other)?;
+  let out = f();
+  let report = guard.report().build().map_err(io::Error::other)?;
+  report.flamegraph(File::create(path)?).map_err(io::Error::other)?;
+  Ok(out)
+}
+
+pub fn run(args: &[String], out: &mut impl Write) -> Result<(), CommandError> {
+  /* the bench command: prints ns per omino for each surround search on a fixed corpus
+  per size. --search picks one of SEARCHES by name, board, stack or translation, or the
+  experimental table, which is left out otherwise, and --flamegraph writes a profile
+  of the whole run as an svg */
+  let mut from = 6;
+  let mut to = 12;
+  let mut corpus_size = 2000;
+  let mut only = None;
+  let mut flamegraph = None;
+  let mut args = args.iter();
+  while let Some(arg) = args.next() {
+    let Some(value) = args.next() else {
+      return Err(usage(format!("{} needs a value", arg)));
+    };
+    let number = || -> Result<u64, CommandError> {
+      value.parse().map_err(|_| usage(format!("bad number {:?}", value)))
+    };
+    let size = || {
+      u8::try_from(number()?)
+        .ok()
+        .filter(|&size| size <= MAX_ENUMERABLE_SIZE)
+        .ok_or_else(|| usage(format!("can only bench up to size {}", MAX_ENUMERABLE_SIZE)))
+    };
+    match arg.as_str() {
+      "--from" => from = size()?,
+      "--to" => to = size()?,
+      "--corpus" => {
+        corpus_size = usize::try_from(number()?).map_err(|_| usage("corpus too large"))?
+      }
+      "--search" => only = Some(value.as_str()),
+      "--flamegraph" => flamegraph = Some(Path::new(value)),
+      _ => return Err(usage(format!("unknown bench option {:?}", arg))),
+    }
+  }
+  let searches: Vec<_> = match only {
+    None => SEARCHES.to_vec(),
+    Some(only) => {
+      SEARCHES.into_iter().chain(EXPERIMENTAL).filter(|(name, _)| only == *name).collect()
+    }
+  };
+  if let (Some(only), true) = (only, searches.is_empty()) {
+    return Err(usage(format!("no search called {:?}", only)));
+  }
+  let run = || -> io::Result<()> {
+    writeln!(out, "{:>4} {:<12} {:>8} {:>12}", "size", "search", "ominos", "ns/omino")?;
+    for size in from..=to {
+      let corpus = corpus(size, corpus_size);
+      for &search in &searches {
+        let result = bench_search(size, search, &corpus, 3);
+        writeln!(
+          out,
+          "{:>4} {:<12} {:>8} {:>12.0}",
+          result.size, result.search, result.ominos, result.ns_per_omino
+        )?;
+      }
+    }
+    Ok(())
+  };
+  profile(flamegraph, run).map_err(|e| failed(format!("could not write flamegraph: {}", e)))??;
+  Ok(())
+}
+
+#[cfg(test)]
+pub mod test {
+  use super::*;
+
+  #[test]
+  fn corpus_and_timing() {
+    let ominos = corpus(6, 50);
+    assert!(ominos.len() <= 50 && ominos.len() >= 40);
+    assert_eq!(ominos, corpus(6, 50));
+    assert_eq!(corpus(4, 100).len(), 19);
+    for search in SEARCHES.into_iter().chain(EXPERIMENTAL) {
+      let result = bench_search(6, search, &ominos, 1);
+      assert_eq!(result.ominos, ominos.len());
+      assert!(result.ns_per_omino > 0.0);
+    }
+  }
+}
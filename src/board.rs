@@ -1,14 +1,46 @@
 use crate::{assemble::*, omino::*};
 use Dir::*;
 
-const OFFSET: usize = 32;
-const MAX_SIZE: usize = OFFSET * 2;
+pub const MAX_SIZE: usize = 64;
+//for ominos too big for the usual board, which is kept small since it's cleared per omino
+pub const LARGE_SIZE: usize = 128;
 
-pub struct Board(pub [[bool; MAX_SIZE]; MAX_SIZE]);
+pub struct Board<const SIZE: usize = MAX_SIZE>(pub(crate) [[bool; SIZE]; SIZE]);
+
+impl<const SIZE: usize> Board<SIZE> {
+  const OFFSET: isize = (SIZE / 2) as isize;
 
-impl Board {
   pub fn empty() -> Self {
-    Self([[false; MAX_SIZE]; MAX_SIZE])
+    Self([[false; SIZE]; SIZE])
+  }
+  pub fn fits(omino: &[FreePoint]) -> bool {
+    /* whether searches for this omino stay on the board. Its rotations and their
+    neighbours reach one further from (0,0) than its furthest point, and copies placed
+    next to the hole reach as far from (0,0) as the omino is wide or tall */
+    let (Some(min_x), Some(max_x)) =
+      (omino.iter().map(|pt| pt.x).min(), omino.iter().map(|pt| pt.x).max())
+    else {
+      return true;
+    };
+    let min_y = omino.iter().map(|pt| pt.y).min().unwrap();
+    let max_y = omino.iter().map(|pt| pt.y).max().unwrap();
+    let furthest = [min_x, max_x, min_y, max_y].iter().map(|&c| (c as isize).abs()).max().unwrap();
+    let extent = (max_x as isize - min_x as isize).max(max_y as isize - min_y as isize) + 1;
+    furthest + 1 < Self::OFFSET && extent < Self::OFFSET
+  }
+  pub fn check_fits(omino: &[FreePoint]) -> Result<(), ShapeError> {
+    //like fits, but saying whether the omino is too large or too far from (0,0)
+    let (width, height) = bounding_box(omino).ok_or(ShapeError::Empty)?;
+    if width.max(height) >= Self::OFFSET as usize {
+      return Err(ShapeError::TooLarge { width, height, limit: Self::OFFSET as usize - 1 });
+    }
+    match omino
+      .iter()
+      .find(|pt| (pt.x as isize).abs().max((pt.y as isize).abs()) + 1 >= Self::OFFSET)
+    {
+      Some(&pt) => Err(ShapeError::PointOutOfRange(pt)),
+      None => Ok(()),
+    }
   }
   pub fn add_always(&mut self, fpl: &[FreePoint]) {
     for pt in fpl {
@@ -24,12 +56,12 @@ impl Board {
     for pt in fpl {
       *self.get_mut(*pt) = true;
     }
-    return true;
+    true
   }
   pub fn undo(&mut self, fpl: &[FreePoint]) {
     for pt in fpl {
-      assert!(self.0[OFFSET + pt.x as usize][OFFSET + pt.y as usize]);
-      self.0[OFFSET + pt.x as usize][OFFSET + pt.y as usize] = false;
+      assert!(self.contains(*pt));
+      *self.get_mut(*pt) = false;
     }
   }
   #[inline]
@@ -45,7 +77,7 @@ impl Board {
     for pt in fpl() {
       *self.get_mut(pt) = true;
     }
-    return true;
+    true
   }
   #[inline]
   pub fn undo_<I>(&mut self, fpl: impl Fn() -> I)
@@ -53,45 +85,85 @@ impl Board {
     I: Iterator<Item = FreePoint>,
   {
     for pt in fpl() {
-      // assert!(self.0[OFFSET + pt.x as usize][OFFSET + pt.y as usize]);
+      // assert!(self.contains(pt));
       *self.get_mut(pt) = false;
     }
   }
   #[inline]
+  fn index(pt: FreePoint) -> (usize, usize) {
+    //negative coordinates must be offset before converting, or debug builds overflow
+    ((Self::OFFSET + pt.x as isize) as usize, (Self::OFFSET + pt.y as isize) as usize)
+  }
+  #[inline]
   pub fn contains(&self, pt: FreePoint) -> bool {
-    // self.0[OFFSET + pt.x as usize][OFFSET + pt.y as usize]
-    unsafe { *self.0.get_unchecked(OFFSET + pt.x as usize).get_unchecked(OFFSET + pt.y as usize) }
+    //panics for a point off the board, see fits
+    let (x, y) = Self::index(pt);
+    self.0[x][y]
   }
   #[inline]
   pub fn get_mut(&mut self, pt: FreePoint) -> &mut bool {
-    unsafe {
-      self.0.get_unchecked_mut(OFFSET + pt.x as usize).get_unchecked_mut(OFFSET + pt.y as usize)
-    }
+    let (x, y) = Self::index(pt);
+    &mut self.0[x][y]
   }
   pub fn next_edge_to_cover(&self) -> Option<Edge> {
     let edges_to_cover =
       [N, E, S, W].map(|d| Edge(offset_in_dir(FreePoint { x: 0, y: 0 }, d), d.flip()));
-    edges_to_cover.iter().copied().filter(|Edge(pt, _d)| !self.contains(*pt)).next()
+    edges_to_cover.iter().copied().find(|Edge(pt, _d)| !self.contains(*pt))
   }
 }
 
-pub fn covers_board(
-  ominos: &[&[FreePoint]; 4],
-  perimeters: &[&[Edge]; 4],
-  board: &mut Board,
+#[derive(Debug, Clone, PartialEq)]
+pub struct Piece {
+  pub omino: FreePointList,
+  //how many copies may be placed, None for any number
+  pub copies: Option<usize>,
+}
+
+pub struct Orientation<'a> {
+  //the index of the piece this is a rotation or reflection of
+  pub piece: usize,
+  pub omino: &'a [FreePoint],
+  pub perimeter: &'a [Edge],
+}
+
+pub fn covers_board<const SIZE: usize>(
+  orientations: &[Orientation],
+  remaining: &mut [Option<usize>],
+  max_placed: usize,
+  board: &mut Board<SIZE>,
+  placed: &mut Vec<(usize, FreePoint)>,
 ) -> bool {
+  /* covers the first uncovered side of the hole with each orientation of a piece that
+  has copies remaining, in each place, and recurses, using at most max_placed copies in
+  all. placed holds the (orientation, translation) of each copy placed so far,
+  and a whole arrangement once this returns true. A single omino is its rotations as
+  the orientations of one piece with any number of copies */
   let Some(edge_to_cover) = board.next_edge_to_cover() else { return true };
   let Edge(pt_to_cover, dir_to_cover) = edge_to_cover;
+  if placed.len() == max_placed {
+    return false;
+  }
 
-  for i in 0..=3 {
-    for &(Edge(fp, d)) in perimeters[i] {
-      if (d == dir_to_cover) {
+  for (i, orientation) in orientations.iter().enumerate() {
+    if remaining[orientation.piece] == Some(0) {
+      continue;
+    }
+    for &Edge(fp, d) in orientation.perimeter {
+      if d == dir_to_cover {
         let translation = translation_of_a_to_b(fp, pt_to_cover);
-        if board.add_(|| translate_omino_iter(&ominos[i], translation)) {
-          if covers_board(ominos, perimeters, board) {
+        if board.add_(|| translate_omino_iter(orientation.omino, translation)) {
+          placed.push((i, translation));
+          if let Some(copies) = remaining[orientation.piece].as_mut() {
+            *copies -= 1;
+          }
+          if covers_board(orientations, remaining, max_placed, board, placed) {
             return true;
           }
-          board.undo_(|| translate_omino_iter(&ominos[i], translation));
+          if let Some(copies) = remaining[orientation.piece].as_mut() {
+            *copies += 1;
+          }
+          placed.pop();
+          board.undo_(|| translate_omino_iter(orientation.omino, translation));
         }
       }
     }
@@ -100,6 +172,156 @@ pub fn covers_board(
   false
 }
 
+fn placed_copies(
+  orientations: &[Orientation],
+  placed: &[(usize, FreePoint)],
+) -> Vec<(usize, FreePointList)> {
+  //the (piece, copy) of each (orientation, translation) covers_board placed
+  placed
+    .iter()
+    .map(|&(i, translation)| {
+      let orientation = &orientations[i];
+      (orientation.piece, translate_omino_iter(orientation.omino, translation).collect())
+    })
+    .collect()
+}
+
+pub fn arrangement_pieces(
+  pieces: &[Piece],
+  mirror: bool,
+  max_placed: usize,
+) -> Option<Vec<(usize, FreePointList)>> {
+  /* whether rotated and translated copies of the pieces, and mirrored ones too if
+  mirror, can surround the hole (0,0) using at most max_placed copies. Returns the
+  placed copies with the index of the piece each is a copy of. Every copy covers at
+  least one side of the hole, so more than 4 are never needed */
+  let mut oriented: Vec<(usize, FreePointList)> = vec![];
+  for (i, piece) in pieces.iter().enumerate() {
+    let mirrored: FreePointList = piece.omino.iter().map(|&pt| mirror_y_axis(pt)).collect();
+    let reflections = if mirror { vec![&piece.omino, &mirrored] } else { vec![&piece.omino] };
+    for omino in reflections {
+      for amt in 0..4 {
+        let rotated = normalize_omino(rotate_omino(omino, amt));
+        if !oriented.contains(&(i, rotated.clone())) {
+          oriented.push((i, rotated));
+        }
+      }
+    }
+  }
+  arrangement_oriented(pieces, &oriented, max_placed)
+}
+
+fn arrangement_oriented(
+  pieces: &[Piece],
+  oriented: &[(usize, FreePointList)],
+  max_placed: usize,
+) -> Option<Vec<(usize, FreePointList)>> {
+  if oriented.iter().all(|(_, omino)| Board::<MAX_SIZE>::fits(omino)) {
+    arrangement_pieces_on::<MAX_SIZE>(pieces, oriented, max_placed)
+  } else {
+    assert!(oriented.iter().all(|(_, omino)| Board::<LARGE_SIZE>::fits(omino)), "piece too large");
+    arrangement_pieces_on::<LARGE_SIZE>(pieces, oriented, max_placed)
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TransformGroup {
+  //copies are only translated, as in find_arrangement_translation
+  Translation,
+  //copies are also rotated, as in has_arrangement_board
+  Rotation,
+  //copies are also mirrored
+  Mirror,
+}
+
+impl TransformGroup {
+  pub const ALL: [TransformGroup; 3] =
+    [TransformGroup::Translation, TransformGroup::Rotation, TransformGroup::Mirror];
+
+  pub fn name(self) -> &'static str {
+    match self {
+      TransformGroup::Translation => "translation",
+      TransformGroup::Rotation => "rotation",
+      TransformGroup::Mirror => "mirror",
+    }
+  }
+}
+
+pub fn arrangement_group(
+  omino: &FreePointList,
+  group: TransformGroup,
+  max_placed: usize,
+) -> Option<Vec<FreePointList>> {
+  //copies of the omino under the group's transforms that surround the hole, at most max_placed
+  let pieces = [Piece { omino: omino.clone(), copies: None }];
+  let placed = match group {
+    TransformGroup::Translation => {
+      arrangement_oriented(&pieces, &[(0, normalize_omino(omino.clone()))], max_placed)
+    }
+    TransformGroup::Rotation => arrangement_pieces(&pieces, false, max_placed),
+    TransformGroup::Mirror => arrangement_pieces(&pieces, true, max_placed),
+  };
+  placed.map(|placed| placed.into_iter().map(|(_, copy)| copy).collect())
+}
+
+fn arrangement_pieces_on<const SIZE: usize>(
+  pieces: &[Piece],
+  oriented: &[(usize, FreePointList)],
+  max_placed: usize,
+) -> Option<Vec<(usize, FreePointList)>> {
+  let perimeters: Vec<_> =
+    oriented.iter().map(|(_, omino)| iter_perimeter_on::<SIZE>(omino)).collect();
+  let orientations: Vec<_> = oriented
+    .iter()
+    .zip(&perimeters)
+    .map(|((piece, omino), perimeter)| Orientation { piece: *piece, omino, perimeter })
+    .collect();
+  let mut remaining: Vec<_> = pieces.iter().map(|piece| piece.copies).collect();
+  let mut board = Board::<SIZE>::empty();
+  let mut placed = vec![];
+  if covers_board(&orientations, &mut remaining, max_placed, &mut board, &mut placed) {
+    Some(placed_copies(&orientations, &placed))
+  } else {
+    None
+  }
+}
+
+pub fn arrangement_board(omino: &FreePointList) -> Option<Vec<FreePointList>> {
+  /*
+  like has_arrangement_board, but returns the placed copies of the omino that
+  surround the hole (0,0). This skips the corner shortcut, since the search finds
+  the corner arrangement anyway when there is one.
+   */
+  if Board::<MAX_SIZE>::fits(omino) {
+    arrangement_on_board::<MAX_SIZE>(omino)
+  } else {
+    assert!(Board::<LARGE_SIZE>::fits(omino), "omino too large to search");
+    arrangement_on_board::<LARGE_SIZE>(omino)
+  }
+}
+
+fn arrangement_on_board<const SIZE: usize>(omino: &FreePointList) -> Option<Vec<FreePointList>> {
+  let rotated_ominos = [0, 1, 2, 3].map(|amt| rotate_omino(omino, amt));
+  let perimeters = rotated_ominos.each_ref().map(iter_perimeter_on::<SIZE>);
+  let orientations = [0, 1, 2, 3].map(|i| Orientation {
+    piece: 0,
+    omino: &rotated_ominos[i],
+    perimeter: &perimeters[i],
+  });
+  let mut board = Board::<SIZE>::empty();
+  let mut placed = vec![];
+  if covers_board(&orientations, &mut [None], 4, &mut board, &mut placed) {
+    Some(placed_copies(&orientations, &placed).into_iter().map(|(_, copy)| copy).collect())
+  } else {
+    None
+  }
+}
+
+pub fn check_searchable(omino: &[FreePoint]) -> Result<(), ShapeError> {
+  //whether the searches can take the omino, on the large board if not the usual one
+  Board::<LARGE_SIZE>::check_fits(omino)
+}
+
 pub fn has_arrangement_board(omino: &FreePointList) -> bool {
   /*
   Given an omino, searches for a set of translation+rotationss which arrange
@@ -113,20 +335,71 @@ pub fn has_arrangement_board(omino: &FreePointList) -> bool {
   if has_corner_arrangement_unsorted(omino) {
     return true;
   }
-  let mut rotated_ominos = [0, 1, 2, 3].map(|amt| rotate_omino(omino, amt));
-  // for i in (0..=3) {
-  //   rotated_ominos[i].sort_unstable();
-  //   if has_corner_arrangement(&rotated_ominos[i]) {
-  //     return true;
-  //   }
-  // }
-  let perimeters = rotated_ominos.each_ref().map(|omino| iter_perimeter(&omino));
-  let mut board = Board::empty();
-  let rotated_ominos_borrows: [&[FreePoint]; 4] = rotated_ominos.each_ref().map(|x| &x[..]);
-  covers_board(&rotated_ominos_borrows, &perimeters.each_ref().map(|x| &x[..]), &mut board)
+  if Board::<MAX_SIZE>::fits(omino) {
+    covers_on_board::<MAX_SIZE>(omino)
+  } else {
+    assert!(Board::<LARGE_SIZE>::fits(omino), "omino too large to search");
+    covers_on_board::<LARGE_SIZE>(omino)
+  }
 }
 
-fn has_corner_arrangement_unsorted(omino: &[FreePoint]) -> bool {
+fn distinct_rotations(rotated_ominos: &[FreePointList; 4]) -> usize {
+  /* how many of the 4 rotations differ by more than a translation, always a prefix of
+  them: 1 for an omino symmetric under a quarter turn, 2 under only a half turn */
+  let normalized = normalize_omino(rotated_ominos[0].clone());
+  if normalize_omino(rotated_ominos[2].clone()) != normalized {
+    4
+  } else if normalize_omino(rotated_ominos[1].clone()) != normalized {
+    2
+  } else {
+    1
+  }
+}
+
+fn covers_on_board<const SIZE: usize>(omino: &FreePointList) -> bool {
+  /*
+  Rotations the omino is symmetric under give the same placements, so only the distinct
+  rotations need searching. Most ominos are surrounded by the first copy tried, so
+  working out which rotations are distinct waits until a first copy has failed, and
+  from then on only first copies of the distinct rotations are tried, each searching
+  only the distinct rotations below it.
+   */
+  let rotated_ominos = [0, 1, 2, 3].map(|amt| rotate_omino(omino, amt));
+  let perimeters = rotated_ominos.each_ref().map(iter_perimeter_on::<SIZE>);
+  let orientations = [0, 1, 2, 3].map(|i| Orientation {
+    piece: 0,
+    omino: &rotated_ominos[i],
+    perimeter: &perimeters[i],
+  });
+
+  let mut board = Board::<SIZE>::empty();
+  let mut placed = Vec::with_capacity(4);
+  let mut distinct = None;
+  let Edge(pt_to_cover, dir_to_cover) = board.next_edge_to_cover().unwrap();
+  for i in 0..4 {
+    if i >= distinct.unwrap_or(4) {
+      break;
+    }
+    for &Edge(fp, d) in orientations[i].perimeter {
+      if d != dir_to_cover {
+        continue;
+      }
+      let translation = translation_of_a_to_b(fp, pt_to_cover);
+      //the board is empty, so the first copy always fits
+      board.add_(|| translate_omino_iter(&rotated_ominos[i], translation));
+      //every other copy covers one of the other 3 sides
+      let orientations = &orientations[..distinct.unwrap_or(4)];
+      if covers_board(orientations, &mut [None], 3, &mut board, &mut placed) {
+        return true;
+      }
+      board.undo_(|| translate_omino_iter(&rotated_ominos[i], translation));
+      distinct.get_or_insert_with(|| distinct_rotations(&rotated_ominos));
+    }
+  }
+  false
+}
+
+pub fn has_corner_arrangement_unsorted(omino: &[FreePoint]) -> bool {
   let mut min_x = omino[0].x;
   let mut max_x = omino[0].x;
   let mut min_y = omino[0].y;
@@ -144,3 +417,86 @@ fn has_corner_arrangement_unsorted(omino: &[FreePoint]) -> bool {
   }
   false
 }
+
+#[cfg(test)]
+pub mod test {
+  use super::*;
+  use crate::{
+    puzzle::test::pentominoes,
+    text::{parse_ascii, parse_ominos},
+  };
+
+  fn is_surrounded(pieces: &[Piece], placed: &[(usize, FreePointList)]) -> bool {
+    //every copy is a rotation or reflection of its piece, and they cover the hole's sides
+    let mut board = Board::<MAX_SIZE>::empty();
+    let disjoint = placed.iter().all(|(_, copy)| board.add(copy));
+    let transforms = |omino: &FreePointList| {
+      let mirrored: FreePointList = omino.iter().map(|&pt| mirror_y_axis(pt)).collect();
+      (0..4)
+        .flat_map(|amt| [rotate_omino(omino, amt), rotate_omino(&mirrored, amt)])
+        .map(normalize_omino)
+        .collect::<Vec<_>>()
+    };
+    disjoint
+      && !board.contains(FreePoint { x: 0, y: 0 })
+      && board.next_edge_to_cover().is_none()
+      && placed.iter().all(|(piece, copy)| {
+        transforms(&pieces[*piece].omino).contains(&normalize_omino(copy.clone()))
+      })
+  }
+
+  #[test]
+  fn pentominoes_surround() {
+    let pieces = pentominoes();
+    //no pentomino has a hole, but the U can wrap 3 sides
+    assert_eq!(arrangement_pieces(&pieces, true, 1), None);
+    let placed = arrangement_pieces(&pieces, true, 2).unwrap();
+    assert_eq!(placed.len(), 2);
+    assert!(is_surrounded(&pieces, &placed));
+  }
+
+  #[test]
+  fn symmetry_pruning() {
+    //searching only the distinct rotations agrees with arrangement_board's search of all 4
+    for size in 1..=9 {
+      enumerate_polyominos(size, |pts| {
+        let omino: FreePointList = pts.iter().map(|&pt| pt.into()).collect();
+        assert_eq!(covers_on_board::<MAX_SIZE>(&omino), arrangement_board(&omino).is_some());
+      });
+    }
+    let cross = ".#####.\n...#...\n#..#..#\n#######\n#..#..#\n...#...\n.#####.";
+    for (picture, distinct) in [(cross, 2), ("##\n##", 1), (".##\n##.", 2), ("##\n#.\n#.", 4)] {
+      let omino = parse_ascii(picture).unwrap();
+      let rotated_ominos = [0, 1, 2, 3].map(|amt| rotate_omino(&omino, amt));
+      assert_eq!(distinct_rotations(&rotated_ominos), distinct, "{}", picture);
+    }
+    assert!(!has_arrangement_board(&parse_ascii(cross).unwrap()));
+  }
+
+  #[test]
+  fn searchable_shapes() {
+    let line = |len: i8, y: i8| (0..len).map(|x| FreePoint { x, y }).collect::<FreePointList>();
+    for omino in [line(40, 0), line(1, 62), line(30, -30)] {
+      assert_eq!(check_searchable(&omino), Ok(()));
+      assert!(Board::<LARGE_SIZE>::fits(&omino));
+    }
+    let too_large = ShapeError::TooLarge { width: 64, height: 1, limit: 63 };
+    assert_eq!(check_searchable(&line(64, 0)), Err(too_large));
+    let far = FreePoint { x: 0, y: 63 };
+    assert_eq!(check_searchable(&line(1, 63)), Err(ShapeError::PointOutOfRange(far)));
+    assert_eq!(check_searchable(&[]), Err(ShapeError::Empty));
+    assert!(!Board::<LARGE_SIZE>::fits(&line(1, 63)));
+  }
+
+  #[test]
+  fn copies_limit() {
+    //a domino can only cover one side of the hole, so it takes 4
+    let domino = Piece { omino: parse_ominos("##").unwrap().remove(0), copies: Some(3) };
+    assert_eq!(arrangement_pieces(std::slice::from_ref(&domino), false, 4), None);
+    let pieces = [Piece { copies: Some(4), ..domino.clone() }];
+    assert!(is_surrounded(&pieces, &arrangement_pieces(&pieces, false, 4).unwrap()));
+    let pieces = [Piece { copies: None, ..domino }];
+    assert_eq!(arrangement_pieces(&pieces, false, 4).unwrap().len(), 4);
+    assert_eq!(arrangement_pieces(&pieces, false, 3), None);
+  }
+}
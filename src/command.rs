@@ -0,0 +1,127 @@
+use std::{
+  fmt,
+  fs::File,
+  io::{self, BufRead, BufReader, Read},
+};
+
+use crate::{
+  board::check_searchable,
+  omino::FreePointList,
+  packed::{OminoReader, MAGIC},
+  text::parse_ominos,
+};
+
+/*
+What the commands of the binary share. Each command is a function in the module whose
+work it does, taking the arguments after the command's name and writing its output to
+out, so the binary only picks the command and turns a `CommandError` into a message
+and an exit code. Progress and other notes go to stderr, so out only gets results.
+ */
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandError {
+  //the arguments were wrong, so the usage is worth printing too
+  Usage(String),
+  //the command couldn't do its work, eg an input couldn't be read
+  Failed(String),
+}
+
+impl fmt::Display for CommandError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      CommandError::Usage(msg) | CommandError::Failed(msg) => f.write_str(msg),
+    }
+  }
+}
+
+impl From<io::Error> for CommandError {
+  fn from(e: io::Error) -> Self {
+    //only writing the output is left to ?, everything else says what it was reading
+    CommandError::Failed(format!("could not write output: {}", e))
+  }
+}
+
+pub fn usage(msg: impl Into<String>) -> CommandError {
+  CommandError::Usage(msg.into())
+}
+
+pub fn failed(msg: impl Into<String>) -> CommandError {
+  CommandError::Failed(msg.into())
+}
+
+pub type Ominos = Box<dyn Iterator<Item = Result<FreePointList, CommandError>>>;
+
+pub fn read_ominos(path: Option<&str>) -> Result<Ominos, CommandError> {
+  /* reads ominos from stdin if no file or "-" is given, either in the packed format
+  from `packed` (streamed, so huge files are fine) or in the text formats from `text`.
+  Ominos too large to search are an error, as the searches can't take them */
+  let input: Box<dyn Read> = match path {
+    None | Some("-") => Box::new(io::stdin()),
+    Some(path) => match File::open(path) {
+      Ok(file) => Box::new(file),
+      Err(e) => return Err(failed(format!("could not open {}: {}", path, e))),
+    },
+  };
+  let mut input = BufReader::new(input);
+  let is_packed = match input.fill_buf() {
+    Ok(buf) => buf.starts_with(MAGIC),
+    Err(e) => return Err(failed(format!("could not read input: {}", e))),
+  };
+  let ominos: Ominos = if is_packed {
+    let reader = OminoReader::new(input).map_err(|e| failed(e.to_string()))?;
+    Box::new(
+      reader.map(|omino| omino.map_err(|e| failed(format!("could not read packed ominos: {}", e)))),
+    )
+  } else {
+    let mut text = String::new();
+    if let Err(e) = input.read_to_string(&mut text) {
+      return Err(failed(format!("could not read input: {}", e)));
+    }
+    match parse_ominos(&text) {
+      Ok(ominos) => Box::new(ominos.into_iter().map(Ok)),
+      Err(e) => return Err(failed(format!("could not parse input: {}", e))),
+    }
+  };
+  Ok(Box::new(ominos.enumerate().map(|(i, omino)| {
+    let omino = omino?;
+    match check_searchable(&omino) {
+      Ok(()) => Ok(omino),
+      Err(e) => Err(failed(format!("omino {} can't be searched: {}", i + 1, e))),
+    }
+  })))
+}
+
+pub fn parse_size_range(
+  args: &[String],
+  mut from: u8,
+  mut to: u8,
+) -> Result<(u8, u8), CommandError> {
+  //--from and --to, each defaulting to the given size
+  let mut args = args.iter();
+  while let Some(arg) = args.next() {
+    let size = args.next().and_then(|n| n.parse().ok());
+    match (arg.as_str(), size) {
+      ("--from", Some(n)) => from = n,
+      ("--to", Some(n)) => to = n,
+      _ => return Err(usage(format!("bad option {:?}", arg))),
+    }
+  }
+  Ok((from, to))
+}
+
+#[cfg(test)]
+pub mod test {
+  use super::*;
+
+  #[test]
+  fn size_ranges() {
+    let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+    assert_eq!(parse_size_range(&args(&[]), 1, 12), Ok((1, 12)));
+    assert_eq!(parse_size_range(&args(&["--to", "5", "--from", "3"]), 1, 12), Ok((3, 5)));
+    assert_eq!(parse_size_range(&args(&["--to"]), 1, 12), Err(usage("bad option \"--to\"")));
+    let too_large = parse_size_range(&args(&["--to", "300"]), 1, 12);
+    assert_eq!(too_large, Err(usage("bad option \"--to\"")));
+    let missing = read_ominos(Some("/nonexistent/ominos.txt")).err().unwrap();
+    assert!(matches!(missing, CommandError::Failed(msg) if msg.starts_with("could not open")));
+  }
+}
@@ -0,0 +1,165 @@
+use std::fmt;
+
+use smallvec::SmallVec;
+
+use crate::lattice::{self, Lattice};
+
+/*
+Polycubes, the 3D version of the hole question: can copies of a polycube, rotated
+and translated, enclose a single empty cube on all 6 of its faces.
+
+Rotations of the cube permute the axes and flip some of them. The 24 proper rotations
+are the ones where an even permutation flips an even number of axes, or an odd
+permutation an odd number. Composing with the inversion (x, y, z) -> (-x, -y, -z)
+gives the other 24 symmetries, the mirror images.
+ */
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug)]
+pub struct FreePoint3 {
+  pub x: i8,
+  pub y: i8,
+  pub z: i8,
+}
+
+pub type FreePoint3List = SmallVec<[FreePoint3; 16]>;
+
+const CUBE_DIRS: [(i8, i8, i8); 6] =
+  [(1, 0, 0), (-1, 0, 0), (0, 1, 0), (0, -1, 0), (0, 0, 1), (0, 0, -1)];
+
+//the permutations of the axes, odd ones marked
+const PERMUTATIONS: [([usize; 3], bool); 6] = [
+  ([0, 1, 2], false),
+  ([1, 2, 0], false),
+  ([2, 0, 1], false),
+  ([0, 2, 1], true),
+  ([1, 0, 2], true),
+  ([2, 1, 0], true),
+];
+const EVEN_FLIPS: [[i8; 3]; 4] = [[1, 1, 1], [1, -1, -1], [-1, 1, -1], [-1, -1, 1]];
+
+impl FreePoint3 {
+  #[inline]
+  pub fn neighbor(self, i: usize) -> FreePoint3 {
+    let (dx, dy, dz) = CUBE_DIRS[i];
+    FreePoint3 { x: self.x + dx, y: self.y + dy, z: self.z + dz }
+  }
+
+  pub fn neighbors(self) -> [FreePoint3; 6] {
+    [0, 1, 2, 3, 4, 5].map(|i| self.neighbor(i))
+  }
+}
+
+impl fmt::Display for FreePoint3 {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{},{},{}", self.x, self.y, self.z)
+  }
+}
+
+pub fn rotate_cube(FreePoint3 { x, y, z }: FreePoint3, rotation: usize) -> FreePoint3 {
+  //one of the 24 rotations, numbered 0..24 with 0 the identity
+  let (perm, odd) = PERMUTATIONS[rotation / 4];
+  let sign = if odd { -1 } else { 1 };
+  let flips = EVEN_FLIPS[rotation % 4];
+  let coords = [x, y, z];
+  FreePoint3 {
+    x: sign * flips[0] * coords[perm[0]],
+    y: sign * flips[1] * coords[perm[1]],
+    z: sign * flips[2] * coords[perm[2]],
+  }
+}
+
+pub struct Cube;
+
+impl Lattice for Cube {
+  type Cell = FreePoint3;
+  type Key = (i8, i8, i8);
+
+  const DIRS: usize = 6;
+  const ROTATIONS: usize = 24;
+  const SYMMETRIES: usize = 48;
+  const ORIGINS: &'static [FreePoint3] = &[FreePoint3 { x: 0, y: 0, z: 0 }];
+  const HOLE: FreePoint3 = FreePoint3 { x: 0, y: 0, z: 0 };
+
+  fn neighbor(cell: FreePoint3, dir: usize) -> FreePoint3 {
+    cell.neighbor(dir)
+  }
+
+  fn sort_key(FreePoint3 { x, y, z }: FreePoint3) -> (i8, i8, i8) {
+    (z, y, x)
+  }
+
+  fn translate(cell: FreePoint3, from: FreePoint3, to: FreePoint3) -> FreePoint3 {
+    FreePoint3 { x: cell.x + to.x - from.x, y: cell.y + to.y - from.y, z: cell.z + to.z - from.z }
+  }
+
+  fn transform(mut cell: FreePoint3, symmetry: usize) -> FreePoint3 {
+    if symmetry >= 24 {
+      cell = FreePoint3 { x: -cell.x, y: -cell.y, z: -cell.z };
+    }
+    rotate_cube(cell, symmetry % 24)
+  }
+}
+
+pub fn rotate_polycube(polycube: &[FreePoint3], rotation: usize) -> FreePoint3List {
+  lattice::transform_shape::<Cube>(polycube, rotation % 24)
+}
+
+pub fn enumerate_polycubes(size: u8, cb: impl FnMut(&[FreePoint3])) {
+  //calls cb with every fixed polycube of the given size
+  lattice::enumerate::<Cube>(size, cb)
+}
+
+pub fn has_arrangement_cube(polycube: &[FreePoint3]) -> bool {
+  /* whether rotated and translated copies of the polycube can cover all 6 faces of a
+  single empty cube without overlapping */
+  lattice::has_arrangement::<Cube>(polycube)
+}
+
+#[cfg(test)]
+pub mod test {
+  use super::*;
+  use rustc_hash::FxHashSet as HashSet;
+
+  #[test]
+  fn polycube_counts() {
+    //fixed polycubes, OEIS A001931, and free ones counting mirror images as the same, A038119
+    let fixed = [1, 3, 15, 86, 534, 3481];
+    let free = [1, 1, 2, 7, 23, 112];
+    for (size, (&fixed, &free)) in (1..).zip(fixed.iter().zip(free.iter())) {
+      let mut found = 0;
+      let mut classes = HashSet::default();
+      enumerate_polycubes(size, |polycube| {
+        found += 1;
+        classes.insert(lattice::canonical::<Cube>(polycube));
+      });
+      assert_eq!(found, fixed, "size {}", size);
+      assert_eq!(classes.len(), free, "size {}", size);
+    }
+  }
+
+  #[test]
+  fn rotation_group() {
+    //a chiral shape has 24 different rotations, and the rotations are closed under composition
+    let chiral: FreePoint3List = [(0, 0, 0), (1, 0, 0), (1, 1, 0), (1, 1, 1)]
+      .iter()
+      .map(|&(x, y, z)| FreePoint3 { x, y, z })
+      .collect();
+    let rotations: HashSet<_> = (0..24).map(|r| rotate_polycube(&chiral, r)).collect();
+    assert_eq!(rotations.len(), 24);
+    for r in 0..24 {
+      for s in 0..24 {
+        assert!(rotations.contains(&rotate_polycube(&rotate_polycube(&chiral, r), s)));
+      }
+    }
+    let mirrored = lattice::transform_shape::<Cube>(&chiral, 24);
+    assert!(!rotations.contains(&mirrored));
+  }
+
+  #[test]
+  fn single_voxel_cavity() {
+    assert!(has_arrangement_cube(&[FreePoint3 { x: 1, y: 0, z: 0 }]));
+    let mut all_enclose = true;
+    enumerate_polycubes(4, |polycube| all_enclose &= has_arrangement_cube(polycube));
+    assert!(all_enclose);
+  }
+}
@@ -0,0 +1,364 @@
+use std::{
+  fs::{File, OpenOptions},
+  io::{self, BufRead, BufReader, BufWriter, Write},
+  path::Path,
+};
+
+use rustc_hash::FxHashMap;
+
+use crate::{
+  assemble::{mirror_y_axis, rotate_omino},
+  board::{
+    arrangement_group, has_arrangement_board, has_corner_arrangement_unsorted, TransformGroup,
+  },
+  omino::{normalize_omino, FreePointList},
+  text::{parse_coords, to_coords},
+};
+
+/*
+A persistent store of search results per free omino, so a run can skip the shapes an
+earlier run already decided. Shapes are keyed by their canonical free form, the least
+of the normalized forms of their 8 rotations and reflections. Whether a shape can
+surround the hole doesn't change when it is rotated or reflected, since the whole
+arrangement can be rotated or reflected with it, so the 8 fixed ominos of a free class
+share one record.
+
+A record holds, for each transform group, whether copies under that group's
+transforms can surround the hole, and if they can the fewest copies that do and one
+arrangement of them, the witness, placed around the hole for the canonical form. It
+also holds the version of the tool that decided it, and a record from another version
+is decided again when it is next asked for, in case the searches have changed.
+
+The file is a text log, one record per line, with the key, the version and then a
+field per group of `TransformGroup::ALL`, separated by tabs. A group's field is `?`
+if it isn't decided, `-` if the shape can't surround the hole, or the witness's
+copies in `text`'s coordinate format separated by `;`. Records are only ever
+appended, and a later line for a key replaces the earlier ones. Each record is written
+and flushed as a whole line, so a run killed part way leaves at most its last line cut
+short, without its newline. Reading drops such a line, and opening the file truncates
+it away so the next record starts on a line of its own, but any other bad line is an
+error.
+
+The search loop only stores the shapes that need a search to decide, since most of
+them have a corner arrangement, which takes less time to check than a lookup and would
+make the store far too large to keep, and it only decides the rotation group, the one
+it asks about. `record` decides the others on request.
+ */
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+const UNDECIDED: &str = "?";
+const UNSURROUNDABLE: &str = "-";
+
+pub fn canonical_form(omino: &FreePointList) -> FreePointList {
+  let mirrored: FreePointList = omino.iter().map(|&pt| mirror_y_axis(pt)).collect();
+  (0..4)
+    .flat_map(|amt| [rotate_omino(omino, amt), rotate_omino(&mirrored, amt)])
+    .map(normalize_omino)
+    .min()
+    .unwrap()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+  Unsurroundable,
+  //an arrangement with the fewest copies
+  Surroundable(Vec<FreePointList>),
+}
+
+impl Verdict {
+  pub fn decide(omino: &FreePointList, group: TransformGroup) -> Self {
+    /* every copy covers at least one side of the hole, so 4 copies always suffice. An
+    unsurroundable shape fails every one of the searches, so for rotations the pruned
+    has_arrangement_board rules it out first with a single search */
+    if group == TransformGroup::Rotation && !has_arrangement_board(omino) {
+      return Verdict::Unsurroundable;
+    }
+    (1..=4)
+      .find_map(|max_placed| arrangement_group(omino, group, max_placed))
+      .map_or(Verdict::Unsurroundable, Verdict::Surroundable)
+  }
+
+  pub fn is_surroundable(&self) -> bool {
+    matches!(self, Verdict::Surroundable(_))
+  }
+
+  pub fn min_copies(&self) -> Option<usize> {
+    self.witness().map(|copies| copies.len())
+  }
+
+  pub fn witness(&self) -> Option<&[FreePointList]> {
+    match self {
+      Verdict::Unsurroundable => None,
+      Verdict::Surroundable(copies) => Some(copies),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ShapeRecord {
+  //a verdict per group of TransformGroup::ALL, None until decided
+  pub verdicts: [Option<Verdict>; 3],
+  pub version: String,
+}
+
+impl ShapeRecord {
+  pub fn verdict(&self, group: TransformGroup) -> Option<&Verdict> {
+    self.verdicts[group as usize].as_ref()
+  }
+
+  fn to_line(&self, key: &FreePointList) -> String {
+    let mut fields = vec![to_coords(key), self.version.clone()];
+    for verdict in &self.verdicts {
+      fields.push(match verdict {
+        None => UNDECIDED.to_string(),
+        Some(Verdict::Unsurroundable) => UNSURROUNDABLE.to_string(),
+        Some(Verdict::Surroundable(copies)) => {
+          copies.iter().map(|copy| to_coords(copy)).collect::<Vec<_>>().join(";")
+        }
+      });
+    }
+    fields.join("\t")
+  }
+
+  fn from_line(line: &str) -> Option<(FreePointList, ShapeRecord)> {
+    let fields: Vec<_> = line.split('\t').collect();
+    let [key, version, verdicts @ ..] = &fields[..] else { return None };
+    if verdicts.len() != TransformGroup::ALL.len() {
+      return None;
+    }
+    let mut record = ShapeRecord { version: version.to_string(), ..Default::default() };
+    for (verdict, field) in record.verdicts.iter_mut().zip(verdicts) {
+      *verdict = match *field {
+        UNDECIDED => None,
+        UNSURROUNDABLE => Some(Verdict::Unsurroundable),
+        copies => Some(Verdict::Surroundable(
+          copies.split(';').map(|copy| parse_coords(copy).ok()).collect::<Option<_>>()?,
+        )),
+      };
+    }
+    Some((parse_coords(key).ok()?, record))
+  }
+}
+
+pub struct ResultDb<W: Write = BufWriter<File>> {
+  records: FxHashMap<FreePointList, ShapeRecord>,
+  log: W,
+  //the first error writing the log, kept until flush so lookups need not fail
+  error: Option<io::Error>,
+  //lookups answered from the store, and shapes decided and added to it
+  pub hits: u64,
+  pub decided: u64,
+}
+
+impl ResultDb {
+  pub fn open(path: &Path) -> io::Result<Self> {
+    //reads the records in the file at path, creating it if there is none
+    let file = OpenOptions::new().read(true).append(true).create(true).open(path)?;
+    let (records, complete) = read_log(BufReader::new(file.try_clone()?))?;
+    if complete < file.metadata()?.len() {
+      file.set_len(complete)?;
+    }
+    Ok(ResultDb { records, log: BufWriter::new(file), error: None, hits: 0, decided: 0 })
+  }
+}
+
+fn read_log(mut reader: impl BufRead) -> io::Result<(FxHashMap<FreePointList, ShapeRecord>, u64)> {
+  //the records, and the length of the log up to the end of its last whole line
+  let mut records = FxHashMap::default();
+  let mut complete = 0;
+  let mut line = vec![];
+  for line_num in 1.. {
+    line.clear();
+    let read = reader.read_until(b'\n', &mut line)?;
+    if line.pop() != Some(b'\n') {
+      //the end of the log, or a last record cut off by a crash
+      break;
+    }
+    complete += read as u64;
+    if line.is_empty() {
+      continue;
+    }
+    let record = std::str::from_utf8(&line).ok().and_then(ShapeRecord::from_line);
+    let Some((key, record)) = record else {
+      let msg = format!("bad record on line {} of the result database", line_num);
+      return Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+    };
+    records.insert(key, record);
+  }
+  Ok((records, complete))
+}
+
+impl<W: Write> ResultDb<W> {
+  pub fn from_log(reader: impl BufRead, log: W) -> io::Result<Self> {
+    /* reads the records written by an earlier run from reader, and appends new ones to
+    log. A last line without its newline is a record cut off by a crash, and is
+    dropped */
+    let (records, _) = read_log(reader)?;
+    Ok(ResultDb { records, log, error: None, hits: 0, decided: 0 })
+  }
+
+  pub fn len(&self) -> usize {
+    self.records.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.records.is_empty()
+  }
+
+  pub fn get(&self, omino: &FreePointList) -> Option<&ShapeRecord> {
+    self.records.get(&canonical_form(omino))
+  }
+
+  pub fn record(&mut self, omino: &FreePointList, groups: &[TransformGroup]) -> &ShapeRecord {
+    /* the record of the omino's free form, deciding the given groups first if they
+    aren't yet, and appending the record to the log if anything was decided */
+    let key = canonical_form(omino);
+    let record = self.records.entry(key.clone()).or_default();
+    if record.version != VERSION {
+      //decided by another version, or not at all
+      *record = ShapeRecord::default();
+    }
+    let undecided: Vec<_> =
+      groups.iter().filter(|&&group| record.verdict(group).is_none()).collect();
+    if undecided.is_empty() {
+      self.hits += 1;
+      return record;
+    }
+    for &group in undecided {
+      record.verdicts[group as usize] = Some(Verdict::decide(&key, group));
+    }
+    record.version = VERSION.to_string();
+    self.decided += 1;
+    if self.error.is_none() {
+      //one write and flush per line, see the top of the file
+      let line = format!("{}\n", record.to_line(&key));
+      if let Err(e) = self.log.write_all(line.as_bytes()).and_then(|()| self.log.flush()) {
+        self.error = Some(e);
+      }
+    }
+    record
+  }
+
+  pub fn surroundable(&mut self, omino: &FreePointList) -> bool {
+    /* whether rotated copies of the omino can surround the hole, as has_arrangement_board
+    answers, deciding only the rotation group for a shape not in the store. Most ominos
+    have a square in a corner of their bounding box, and 4 copies around the hole then
+    always work, which is quicker to check than a lookup, so those are never stored */
+    if has_corner_arrangement_unsorted(omino) {
+      return true;
+    }
+    let record = self.record(omino, &[TransformGroup::Rotation]);
+    record.verdict(TransformGroup::Rotation).unwrap().is_surroundable()
+  }
+
+  pub fn flush(&mut self) -> io::Result<()> {
+    if let Some(e) = self.error.take() {
+      return Err(e);
+    }
+    self.log.flush()
+  }
+}
+
+#[cfg(test)]
+pub mod test {
+  use super::*;
+  use crate::{oeis::A000105, omino::enumerate_polyominos, text::parse_ascii, verify_arrangement};
+  use rustc_hash::FxHashSet as HashSet;
+
+  #[test]
+  fn canonical_forms() {
+    let l = parse_ascii("#.\n#.\n##").unwrap();
+    let j = parse_ascii("###\n#..").unwrap();
+    assert_eq!(canonical_form(&l), canonical_form(&j));
+    assert_eq!(canonical_form(&canonical_form(&l)), canonical_form(&l));
+    assert_ne!(canonical_form(&l), canonical_form(&parse_ascii("####").unwrap()));
+  }
+
+  #[test]
+  fn verdicts_by_group() {
+    //a domino takes 4 copies, and the U pentomino 2
+    let domino = Verdict::decide(&parse_ascii("##").unwrap(), TransformGroup::Rotation);
+    assert_eq!(domino.min_copies(), Some(4));
+    let u = parse_ascii("#.#\n###").unwrap();
+    assert_eq!(Verdict::decide(&u, TransformGroup::Rotation).min_copies(), Some(2));
+    assert_eq!(Verdict::decide(&u, TransformGroup::Translation).min_copies(), Some(2));
+    let square = parse_ascii("##\n##").unwrap();
+    assert_eq!(Verdict::decide(&square, TransformGroup::Translation).min_copies(), Some(4));
+    assert_eq!(Verdict::decide(&square, TransformGroup::Mirror).min_copies(), Some(4));
+  }
+
+  #[test]
+  fn records_persist() {
+    let mut db = ResultDb::from_log(io::empty(), vec![]).unwrap();
+    let mut lookups = 0;
+    let mut shapes = HashSet::default();
+    enumerate_polyominos(7, |pts| {
+      let omino: FreePointList = pts.iter().map(|&pt| pt.into()).collect();
+      assert_eq!(db.surroundable(&omino), has_arrangement_board(&omino));
+      if !has_corner_arrangement_unsorted(&omino) {
+        lookups += 1;
+        shapes.insert(canonical_form(&omino));
+      }
+    });
+    //only the free forms of ominos without a corner arrangement are stored, once each
+    assert_eq!((db.len(), db.decided), (shapes.len(), shapes.len() as u64));
+    assert_eq!(db.hits, lookups - shapes.len() as u64);
+    for key in &shapes {
+      let record = db.get(key).unwrap();
+      assert!(record.verdict(TransformGroup::Rotation).is_some());
+      assert_eq!(record.verdict(TransformGroup::Translation), None);
+    }
+    enumerate_polyominos(7, |pts| {
+      db.record(&pts.iter().map(|&pt| pt.into()).collect(), &TransformGroup::ALL);
+    });
+    assert_eq!(db.len() as u64, A000105[7]);
+    for (key, record) in &db.records {
+      let verdict = record.verdict(TransformGroup::Rotation).unwrap();
+      if let Some(witness) = verdict.witness() {
+        assert!(verify_arrangement(key, witness));
+      }
+      assert_eq!(verdict.is_surroundable(), has_arrangement_board(key));
+      //a larger group never needs more copies
+      let copies = TransformGroup::ALL.map(|group| record.verdict(group).unwrap().min_copies());
+      let needed = |copies: Option<usize>| copies.unwrap_or(usize::MAX);
+      assert!(needed(copies[0]) >= needed(copies[1]) && needed(copies[1]) >= needed(copies[2]));
+    }
+
+    db.flush().unwrap();
+    let log = db.log.clone();
+    let reopened = ResultDb::from_log(&log[..], vec![]).unwrap();
+    assert_eq!(reopened.records, db.records);
+    assert!(ResultDb::from_log(&b"0,0\tnot a record\n"[..], vec![]).is_err());
+  }
+
+  #[test]
+  fn torn_and_stale_records() {
+    let mut db = ResultDb::from_log(io::empty(), vec![]).unwrap();
+    let u = parse_ascii("#.#\n###").unwrap();
+    db.record(&u, &[TransformGroup::Rotation]);
+    let line = String::from_utf8(db.log.clone()).unwrap();
+    //a run killed part way through its next record leaves part of a line, which is dropped
+    let torn = format!("{}{}", line, &line[..line.len() / 2]);
+    let dir = std::env::temp_dir().join(format!("polyominoes-db-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("results.db");
+    std::fs::write(&path, &torn).unwrap();
+    let mut reopened = ResultDb::open(&path).unwrap();
+    assert_eq!(reopened.records, db.records);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), line);
+    reopened.record(&parse_ascii("##").unwrap(), &[TransformGroup::Rotation]);
+    assert_eq!(ResultDb::open(&path).unwrap().len(), 2);
+    //a bad line with its newline is not a torn write
+    std::fs::write(&path, format!("{}{}", &line[..line.len() / 2], line)).unwrap();
+    assert!(ResultDb::open(&path).is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    //a record from another version is decided again when asked for
+    let old = line.replacen(VERSION, "0.0.0", 1);
+    let mut db = ResultDb::from_log(old.as_bytes(), vec![]).unwrap();
+    assert_eq!(db.get(&u).unwrap().version, "0.0.0");
+    db.record(&u, &[TransformGroup::Rotation]);
+    assert_eq!((db.hits, db.decided), (0, 1));
+    assert_eq!(db.get(&u).unwrap().version, VERSION);
+  }
+}
@@ -0,0 +1,154 @@
+use std::fmt;
+
+use smallvec::SmallVec;
+
+use crate::lattice::{self, Lattice};
+
+/*
+Polyhexes, ie polyominos on the hexagonal lattice.
+
+A hexagon is named by axial coordinates (q, r), the third cube coordinate being
+-q - r. Its 6 neighbours are in the directions of HEX_DIRS, in order around it from
+east, so direction i + 3 is opposite direction i. Every hexagon translates onto every
+other, so unlike triangles there is a single translation class.
+
+Rotating by 60 degrees is (q, r) -> (-r, q + r), and swapping q and r is a
+reflection, which together give the 12 symmetries of the lattice.
+ */
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug)]
+pub struct HexPoint {
+  pub q: i8,
+  pub r: i8,
+}
+
+pub type HexPointList = SmallVec<[HexPoint; 16]>;
+
+const HEX_DIRS: [(i8, i8); 6] = [(1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1), (0, 1)];
+
+impl HexPoint {
+  #[inline]
+  pub fn neighbor(self, i: usize) -> HexPoint {
+    let (dq, dr) = HEX_DIRS[i];
+    HexPoint { q: self.q + dq, r: self.r + dr }
+  }
+
+  pub fn neighbors(self) -> [HexPoint; 6] {
+    [0, 1, 2, 3, 4, 5].map(|i| self.neighbor(i))
+  }
+}
+
+impl fmt::Display for HexPoint {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{},{}", self.q, self.r)
+  }
+}
+
+pub fn rotate_60(HexPoint { q, r }: HexPoint) -> HexPoint {
+  HexPoint { q: -r, r: q + r }
+}
+
+pub fn reflect(HexPoint { q, r }: HexPoint) -> HexPoint {
+  HexPoint { q: r, r: q }
+}
+
+pub struct Hex;
+
+impl Lattice for Hex {
+  type Cell = HexPoint;
+  type Key = (i8, i8);
+
+  const DIRS: usize = 6;
+  const ROTATIONS: usize = 6;
+  const SYMMETRIES: usize = 12;
+  const ORIGINS: &'static [HexPoint] = &[HexPoint { q: 0, r: 0 }];
+  const HOLE: HexPoint = HexPoint { q: 0, r: 0 };
+
+  fn neighbor(cell: HexPoint, dir: usize) -> HexPoint {
+    cell.neighbor(dir)
+  }
+
+  fn sort_key(HexPoint { q, r }: HexPoint) -> (i8, i8) {
+    (r, q)
+  }
+
+  fn translate(cell: HexPoint, from: HexPoint, to: HexPoint) -> HexPoint {
+    HexPoint { q: cell.q + to.q - from.q, r: cell.r + to.r - from.r }
+  }
+
+  fn transform(mut cell: HexPoint, symmetry: usize) -> HexPoint {
+    if symmetry >= 6 {
+      cell = reflect(cell);
+    }
+    for _ in 0..symmetry % 6 {
+      cell = rotate_60(cell);
+    }
+    cell
+  }
+}
+
+pub fn rotate_hex(hex: &[HexPoint], rotate_amt: u8) -> HexPointList {
+  //rotate_amt is the number of 60 degree turns
+  lattice::transform_shape::<Hex>(hex, (rotate_amt % 6).into())
+}
+
+pub fn enumerate_polyhexes(size: u8, cb: impl FnMut(&[HexPoint])) {
+  //calls cb with every fixed polyhex of the given size
+  lattice::enumerate::<Hex>(size, cb)
+}
+
+pub fn hex_perimeter(hex: &[HexPoint]) -> Vec<(HexPoint, usize)> {
+  //every (hexagon, edge) pair where the hexagon across the edge isn't in the polyhex
+  lattice::perimeter::<Hex>(hex)
+}
+
+pub fn has_arrangement_hex(hex: &[HexPoint]) -> bool {
+  /* whether copies of the polyhex, rotated by multiples of 60 degrees and translated,
+  can surround a single hexagon without overlapping */
+  lattice::has_arrangement::<Hex>(hex)
+}
+
+#[cfg(test)]
+pub mod test {
+  use super::*;
+  use rustc_hash::FxHashSet as HashSet;
+
+  #[test]
+  fn polyhex_counts() {
+    //fixed polyhexes, OEIS A001207, and free ones, OEIS A000228
+    let fixed = [1, 3, 11, 44, 186, 814, 3652];
+    let free = [1, 1, 3, 7, 22, 82, 333];
+    for (size, (&fixed, &free)) in (1..).zip(fixed.iter().zip(free.iter())) {
+      let mut found = 0;
+      let mut classes = HashSet::default();
+      enumerate_polyhexes(size, |hex| {
+        found += 1;
+        classes.insert(lattice::canonical::<Hex>(hex));
+      });
+      assert_eq!(found, fixed, "size {}", size);
+      assert_eq!(classes.len(), free, "size {}", size);
+    }
+  }
+
+  #[test]
+  fn rotations() {
+    let pt = HexPoint { q: 2, r: -1 };
+    assert_eq!(rotate_hex(&[pt], 6)[0], pt);
+    assert_eq!(rotate_hex(&[pt], 3)[0], HexPoint { q: -2, r: 1 });
+    for i in 0..6 {
+      assert_eq!(rotate_60(pt.neighbor(i)), rotate_60(pt).neighbor((i + 5) % 6));
+      assert!(reflect(pt.neighbor(i)).neighbors().contains(&reflect(pt)));
+    }
+    //a straight line of 2 has 10 perimeter edges
+    assert_eq!(hex_perimeter(&[HexPoint { q: 0, r: 0 }, HexPoint { q: 1, r: 0 }]).len(), 10);
+  }
+
+  #[test]
+  fn single_hex_hole() {
+    //6 single hexagons fill the ring around the hole
+    assert!(has_arrangement_hex(&[HexPoint { q: 1, r: 0 }]));
+    let mut all_surround = true;
+    enumerate_polyhexes(4, |hex| all_surround &= has_arrangement_hex(hex));
+    assert!(all_surround);
+  }
+}
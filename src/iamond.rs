@@ -0,0 +1,195 @@
+use std::fmt;
+
+use smallvec::SmallVec;
+
+use crate::lattice::{self, Lattice};
+
+/*
+Polyiamonds, ie polyominos on the triangular lattice.
+
+A triangle is named by three integers (a, b, c) with a + b + c either 1 (an up
+triangle) or 2 (a down triangle). Each coordinate counts strips of triangles in one of
+the three directions of the lattice's lines, so an up triangle's 3 neighbours are
+found by adding 1 to one of its coordinates, and a down triangle's by subtracting 1.
+Edge i of a triangle is the one shared with the neighbour that differs in coordinate
+i, so edge i of an up triangle is glued to edge i of a down triangle.
+
+In these coordinates rotating by 120 degrees is cycling the coordinates, and rotating
+by 60 degrees is (a, b, c) -> (1 - c, 1 - a, 1 - b), which swaps up and down.
+Translations add a vector summing to 0, so they keep a triangle's orientation, and
+swapping a and b is a reflection. The enumeration and surround search are the generic
+ones in `lattice`.
+ */
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug)]
+pub struct TriPoint {
+  pub a: i8,
+  pub b: i8,
+  pub c: i8,
+}
+
+pub type TriPointList = SmallVec<[TriPoint; 16]>;
+
+impl TriPoint {
+  pub fn is_up(self) -> bool {
+    self.a + self.b + self.c == 1
+  }
+
+  #[inline]
+  pub fn neighbor(self, i: usize) -> TriPoint {
+    let d = if self.is_up() { 1 } else { -1 };
+    let TriPoint { a, b, c } = self;
+    match i {
+      0 => TriPoint { a: a + d, b, c },
+      1 => TriPoint { a, b: b + d, c },
+      2 => TriPoint { a, b, c: c + d },
+      _ => unreachable!("triangles have 3 edges"),
+    }
+  }
+
+  pub fn neighbors(self) -> [TriPoint; 3] {
+    [0, 1, 2].map(|i| self.neighbor(i))
+  }
+
+  fn sort_key(self) -> (i8, i8) {
+    /* a total order that translations preserve: rows of constant c, and along a row
+    a - b goes up by 1 per triangle. (c, a - b) and the orientation determine the
+    triangle, and the orientation is fixed by the parity of c + a - b */
+    (self.c, self.a - self.b)
+  }
+}
+
+pub fn rotate_60(TriPoint { a, b, c }: TriPoint) -> TriPoint {
+  TriPoint { a: 1 - c, b: 1 - a, c: 1 - b }
+}
+
+pub fn reflect(TriPoint { a, b, c }: TriPoint) -> TriPoint {
+  TriPoint { a: b, b: a, c }
+}
+
+impl fmt::Display for TriPoint {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{},{},{}", self.a, self.b, self.c)
+  }
+}
+
+pub struct Triangle;
+
+impl Lattice for Triangle {
+  type Cell = TriPoint;
+  type Key = (i8, i8);
+
+  const DIRS: usize = 3;
+  const ROTATIONS: usize = 6;
+  const SYMMETRIES: usize = 12;
+  //translations can't turn an up triangle into a down one
+  const ORIGINS: &'static [TriPoint] =
+    &[TriPoint { a: 0, b: 0, c: 1 }, TriPoint { a: 1, b: 0, c: 1 }];
+  //a down hole is a rotated up hole, so it's enough to look at an up one
+  const HOLE: TriPoint = TriPoint { a: 0, b: 0, c: 1 };
+
+  fn neighbor(cell: TriPoint, dir: usize) -> TriPoint {
+    cell.neighbor(dir)
+  }
+
+  fn sort_key(cell: TriPoint) -> (i8, i8) {
+    cell.sort_key()
+  }
+
+  fn same_class(a: TriPoint, b: TriPoint) -> bool {
+    a.is_up() == b.is_up()
+  }
+
+  fn translate(cell: TriPoint, from: TriPoint, to: TriPoint) -> TriPoint {
+    TriPoint { a: cell.a + to.a - from.a, b: cell.b + to.b - from.b, c: cell.c + to.c - from.c }
+  }
+
+  fn transform(mut cell: TriPoint, symmetry: usize) -> TriPoint {
+    if symmetry >= 6 {
+      cell = reflect(cell);
+    }
+    for _ in 0..symmetry % 6 {
+      cell = rotate_60(cell);
+    }
+    cell
+  }
+}
+
+pub fn rotate_iamond(iamond: &[TriPoint], rotate_amt: u8) -> TriPointList {
+  //rotate_amt is the number of 60 degree turns
+  lattice::transform_shape::<Triangle>(iamond, (rotate_amt % 6).into())
+}
+
+pub fn translate_iamond(iamond: &[TriPoint], a: TriPoint, b: TriPoint) -> TriPointList {
+  //translates iamond so that the triangle a lands on b. a and b must point the same way
+  debug_assert!(Triangle::same_class(a, b));
+  iamond.iter().map(|&pt| Triangle::translate(pt, a, b)).collect()
+}
+
+pub fn enumerate_polyiamonds(size: u8, cb: impl FnMut(&[TriPoint])) {
+  //calls cb with every fixed polyiamond of the given size
+  lattice::enumerate::<Triangle>(size, cb)
+}
+
+pub fn iamond_perimeter(iamond: &[TriPoint]) -> Vec<(TriPoint, usize)> {
+  //every (triangle, edge) pair where the triangle across the edge isn't in the iamond
+  lattice::perimeter::<Triangle>(iamond)
+}
+
+pub fn has_arrangement_iamond(iamond: &[TriPoint]) -> bool {
+  /* whether copies of the iamond, rotated by multiples of 60 degrees and translated,
+  can surround a single triangle without overlapping */
+  lattice::has_arrangement::<Triangle>(iamond)
+}
+
+#[cfg(test)]
+pub mod test {
+  use super::*;
+  use rustc_hash::FxHashSet as HashSet;
+
+  #[test]
+  fn polyiamond_counts() {
+    //fixed polyiamonds, OEIS A001420
+    let expected = [2, 3, 6, 14, 36, 94, 250, 675];
+    for (size, &count) in (1..).zip(expected.iter()) {
+      let mut found = 0;
+      enumerate_polyiamonds(size, |_| found += 1);
+      assert_eq!(found, count, "size {}", size);
+    }
+  }
+
+  #[test]
+  fn rotations() {
+    let pt = TriPoint { a: 3, b: -1, c: -1 };
+    assert!(pt.is_up());
+    assert!(!rotate_60(pt).is_up());
+    assert_eq!(rotate_iamond(&[pt], 6)[0], pt);
+    assert_eq!(rotate_iamond(&[pt], 2)[0], TriPoint { a: -1, b: -1, c: 3 });
+    //rotating keeps neighbours next to each other
+    for i in 0..3 {
+      assert!(rotate_60(pt.neighbor(i)).neighbors().contains(&rotate_60(pt)));
+    }
+  }
+
+  #[test]
+  fn small_iamonds_surround() {
+    let triangle = [TriPoint { a: 1, b: 0, c: 1 }];
+    assert!(has_arrangement_iamond(&triangle));
+    let mut all_surround = true;
+    enumerate_polyiamonds(5, |iamond| all_surround &= has_arrangement_iamond(iamond));
+    assert!(all_surround);
+  }
+
+  #[test]
+  fn free_polyiamonds() {
+    //free polyiamonds, OEIS A000577
+    let expected = [1, 1, 1, 3, 4, 12, 24, 66];
+    for (size, &count) in (1..).zip(expected.iter()) {
+      let mut free = HashSet::default();
+      enumerate_polyiamonds(size, |iamond| {
+        free.insert(lattice::canonical::<Triangle>(iamond));
+      });
+      assert_eq!(free.len(), count, "size {}", size);
+    }
+  }
+}
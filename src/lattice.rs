@@ -0,0 +1,324 @@
+use std::{
+  fmt::{Debug, Display},
+  hash::Hash,
+  io::Write,
+};
+
+use itertools::Itertools;
+use rustc_hash::FxHashSet as HashSet;
+use smallvec::{smallvec, SmallVec};
+
+use crate::{
+  assemble::{mirror_y_axis, rotate_cw},
+  command::{parse_size_range, usage, CommandError},
+  cube::Cube,
+  hex::Hex,
+  iamond::Triangle,
+  omino::{offset_in_dir, Dir, FreePoint},
+};
+use Dir::*;
+
+/*
+The enumeration and surround search written once for any lattice of cells, so the
+square grid, triangles (`iamond`) and hexagons (`hex`) share the same code. The square
+grid also has the much faster specialised versions in `omino` and `board`.
+
+A lattice says what its cells are, how to step from a cell across each of its edges,
+how to translate, and its symmetries. Translations may not be able to move every cell
+onto every other, eg a triangle pointing up never translates onto one pointing down,
+so cells come in translation classes, with one origin cell per class.
+ */
+
+pub trait Lattice {
+  type Cell: Copy + Eq + Ord + Hash + Debug + Display + 'static;
+  type Key: Ord;
+
+  //edges per cell
+  const DIRS: usize;
+  //the symmetries are numbered 0..SYMMETRIES, and the first ROTATIONS of them are rotations
+  const ROTATIONS: usize;
+  const SYMMETRIES: usize;
+  //one cell per translation class
+  const ORIGINS: &'static [Self::Cell];
+  //the cell surround searches try to enclose
+  const HOLE: Self::Cell;
+
+  fn neighbor(cell: Self::Cell, dir: usize) -> Self::Cell;
+  //a total order on cells that translations preserve
+  fn sort_key(cell: Self::Cell) -> Self::Key;
+  //whether a translation takes a to b
+  fn same_class(_a: Self::Cell, _b: Self::Cell) -> bool {
+    true
+  }
+  //the translation taking from to to, applied to cell
+  fn translate(cell: Self::Cell, from: Self::Cell, to: Self::Cell) -> Self::Cell;
+  fn transform(cell: Self::Cell, symmetry: usize) -> Self::Cell;
+}
+
+pub type Shape<L> = SmallVec<[<L as Lattice>::Cell; 16]>;
+
+fn enumerate_recursion<L: Lattice>(
+  cb: &mut impl FnMut(&[L::Cell]),
+  seen: &mut HashSet<L::Cell>,
+  mut untried_set: Shape<L>,
+  occupied_set: &mut Shape<L>,
+  origin: L::Cell,
+  size: usize,
+) {
+  while let Some(next_tile) = untried_set.pop() {
+    occupied_set.push(next_tile);
+    if occupied_set.len() == size {
+      cb(occupied_set);
+    } else {
+      let mut new_untried_set = untried_set.clone();
+      let mut new_neighbors: SmallVec<[L::Cell; 6]> = smallvec![];
+      for dir in 0..L::DIRS {
+        let neighbor = L::neighbor(next_tile, dir);
+        //only cells after the origin, so each shape is found from its first cell
+        if L::sort_key(neighbor) > L::sort_key(origin) && seen.insert(neighbor) {
+          new_untried_set.push(neighbor);
+          new_neighbors.push(neighbor);
+        }
+      }
+      enumerate_recursion::<L>(cb, seen, new_untried_set, occupied_set, origin, size);
+      for neighbor in new_neighbors {
+        seen.remove(&neighbor);
+      }
+    }
+    occupied_set.pop();
+  }
+}
+
+pub fn enumerate<L: Lattice>(size: u8, mut cb: impl FnMut(&[L::Cell])) {
+  /* calls cb with every fixed shape of the given size, by Redelmeier's method as in
+  omino::enumerate_polyominos, once for each translation class of a shape's first cell */
+  for &origin in L::ORIGINS {
+    let mut seen = HashSet::default();
+    seen.insert(origin);
+    let mut occupied_set = smallvec![];
+    enumerate_recursion::<L>(
+      &mut cb,
+      &mut seen,
+      smallvec![origin],
+      &mut occupied_set,
+      origin,
+      size.into(),
+    );
+  }
+}
+
+pub fn transform_shape<L: Lattice>(shape: &[L::Cell], symmetry: usize) -> Shape<L> {
+  shape.iter().map(|&cell| L::transform(cell, symmetry)).collect()
+}
+
+pub fn normalize<L: Lattice>(shape: &[L::Cell]) -> Shape<L> {
+  //translates the first cell of the shape to the origin of its class, and sorts it
+  let first = *shape.iter().min_by_key(|&&cell| L::sort_key(cell)).unwrap();
+  let origin = *L::ORIGINS.iter().find(|&&origin| L::same_class(first, origin)).unwrap();
+  let mut out: Shape<L> = shape.iter().map(|&cell| L::translate(cell, first, origin)).collect();
+  out.sort();
+  out
+}
+
+pub fn canonical<L: Lattice>(shape: &[L::Cell]) -> Shape<L> {
+  //the same for every shape in a free equivalence class, ie up to all symmetries
+  (0..L::SYMMETRIES).map(|s| normalize::<L>(&transform_shape::<L>(shape, s))).min().unwrap()
+}
+
+pub fn perimeter<L: Lattice>(shape: &[L::Cell]) -> Vec<(L::Cell, usize)> {
+  //every (cell, edge) pair where the cell across the edge isn't in the shape
+  let cells: HashSet<L::Cell> = shape.iter().copied().collect();
+  let mut out = vec![];
+  for &cell in shape {
+    for dir in 0..L::DIRS {
+      if !cells.contains(&L::neighbor(cell, dir)) {
+        out.push((cell, dir));
+      }
+    }
+  }
+  out
+}
+
+fn covers_hole<L: Lattice>(
+  shapes: &[Shape<L>],
+  perimeters: &[Vec<(L::Cell, usize)>],
+  board: &mut HashSet<L::Cell>,
+) -> bool {
+  /* board::covers_board for any lattice: cover the hole's edges in order, each by a
+  perimeter edge of some transformed copy, backtracking when nothing fits */
+  let hole = L::HOLE;
+  let Some(pt_to_cover) =
+    (0..L::DIRS).map(|dir| L::neighbor(hole, dir)).find(|pt| !board.contains(pt))
+  else {
+    return true;
+  };
+
+  for (shape, perimeter) in shapes.iter().zip(perimeters) {
+    for &(cell, dir) in perimeter {
+      //moving cell onto pt_to_cover must put the far side of its edge on the hole
+      if !L::same_class(cell, pt_to_cover)
+        || L::translate(L::neighbor(cell, dir), cell, pt_to_cover) != hole
+      {
+        continue;
+      }
+      let moved: Shape<L> = shape.iter().map(|&c| L::translate(c, cell, pt_to_cover)).collect();
+      if moved.iter().all(|c| !board.contains(c)) {
+        board.extend(moved.iter().copied());
+        if covers_hole::<L>(shapes, perimeters, board) {
+          return true;
+        }
+        for c in &moved {
+          board.remove(c);
+        }
+      }
+    }
+  }
+
+  false
+}
+
+pub fn has_arrangement<L: Lattice>(shape: &[L::Cell]) -> bool {
+  /* whether rotated and translated copies of the shape can surround L::HOLE without
+  overlapping, the question board::has_arrangement_board answers on the square grid */
+  let rotated: Vec<Shape<L>> = (0..L::ROTATIONS).map(|r| transform_shape::<L>(shape, r)).collect();
+  let perimeters: Vec<_> = rotated.iter().map(|shape| perimeter::<L>(shape)).collect();
+  let mut board = HashSet::default();
+  covers_hole::<L>(&rotated, &perimeters, &mut board)
+}
+
+pub struct Square;
+
+impl Lattice for Square {
+  type Cell = FreePoint;
+  type Key = (i8, i8);
+
+  const DIRS: usize = 4;
+  const ROTATIONS: usize = 4;
+  const SYMMETRIES: usize = 8;
+  const ORIGINS: &'static [FreePoint] = &[FreePoint { x: 0, y: 0 }];
+  const HOLE: FreePoint = FreePoint { x: 0, y: 0 };
+
+  fn neighbor(cell: FreePoint, dir: usize) -> FreePoint {
+    offset_in_dir(cell, [N, E, S, W][dir])
+  }
+
+  fn sort_key(FreePoint { x, y }: FreePoint) -> (i8, i8) {
+    (y, x)
+  }
+
+  fn translate(cell: FreePoint, from: FreePoint, to: FreePoint) -> FreePoint {
+    FreePoint { x: cell.x + to.x - from.x, y: cell.y + to.y - from.y }
+  }
+
+  fn transform(mut cell: FreePoint, symmetry: usize) -> FreePoint {
+    if symmetry >= 4 {
+      cell = mirror_y_axis(cell);
+    }
+    for _ in 0..symmetry % 4 {
+      cell = rotate_cw(cell);
+    }
+    cell
+  }
+}
+
+pub fn run(args: &[String], out: &mut impl Write) -> Result<(), CommandError> {
+  //the surround search for shapes on the other lattices, printing any failures
+  let Some(lattice) = args.first() else { return Err(usage("lattice needs a lattice name")) };
+  match lattice.as_str() {
+    "triangle" => {
+      let (from, to) = parse_size_range(&args[1..], 1, 12)?;
+      search::<Triangle>("iamonds", from, to, out)
+    }
+    "hex" => {
+      let (from, to) = parse_size_range(&args[1..], 1, 10)?;
+      search::<Hex>("hexes", from, to, out)
+    }
+    "cube" => {
+      let (from, to) = parse_size_range(&args[1..], 1, 6)?;
+      search::<Cube>("cubes", from, to, out)
+    }
+    other => Err(usage(format!("unknown lattice {:?}", other))),
+  }
+}
+
+fn search<L: Lattice>(
+  name: &str,
+  from: u8,
+  to: u8,
+  out: &mut impl Write,
+) -> Result<(), CommandError> {
+  for size in from..=to {
+    let mut count = 0;
+    let mut failures = vec![];
+    enumerate::<L>(size, |shape| {
+      count += 1;
+      if !has_arrangement::<L>(shape) {
+        failures.push(shape.iter().join(" "));
+      }
+    });
+    writeln!(out, "{} {}, count: {} unsurroundable: {}", size, name, count, failures.len())?;
+    for failure in failures {
+      writeln!(out, "  {}", failure)?;
+    }
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+pub mod test {
+  use super::*;
+  use crate::{cube::Cube, hex::Hex, iamond::Triangle, omino::enumerate_polyominos};
+
+  fn check_symmetries<L: Lattice>() {
+    /* every symmetry keeps neighbouring cells neighbours, symmetry 0 is the identity,
+    and no two symmetries move the cells around the origins the same way */
+    let mut cells: Vec<L::Cell> = L::ORIGINS.to_vec();
+    for _ in 0..2 {
+      let next: Vec<_> = cells
+        .iter()
+        .flat_map(|&cell| (0..L::DIRS).map(move |dir| L::neighbor(cell, dir)))
+        .collect();
+      cells.extend(next);
+    }
+    cells.sort();
+    cells.dedup();
+    let mut images = HashSet::default();
+    for symmetry in 0..L::SYMMETRIES {
+      for &cell in &cells {
+        let image = L::transform(cell, symmetry);
+        for dir in 0..L::DIRS {
+          let neighbor = L::transform(L::neighbor(cell, dir), symmetry);
+          assert!((0..L::DIRS).any(|d| L::neighbor(image, d) == neighbor), "{}", symmetry);
+        }
+      }
+      assert!(images.insert(transform_shape::<L>(&cells, symmetry)), "{}", symmetry);
+    }
+    assert_eq!(transform_shape::<L>(&cells, 0)[..], cells[..]);
+  }
+
+  #[test]
+  fn lattice_symmetries() {
+    check_symmetries::<Square>();
+    check_symmetries::<Triangle>();
+    check_symmetries::<Hex>();
+    check_symmetries::<Cube>();
+  }
+
+  #[test]
+  fn generic_square_matches_omino() {
+    for size in 1..=7 {
+      let mut fixed = 0;
+      enumerate_polyominos(size, |_| fixed += 1);
+      let mut generic = 0;
+      let mut free = HashSet::default();
+      enumerate::<Square>(size, |shape| {
+        generic += 1;
+        free.insert(canonical::<Square>(shape));
+        assert!(has_arrangement::<Square>(shape));
+      });
+      assert_eq!(generic, fixed);
+      //free polyominoes, OEIS A000105
+      assert_eq!(free.len(), [1, 1, 2, 5, 12, 35, 108][usize::from(size) - 1]);
+    }
+  }
+}
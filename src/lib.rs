@@ -0,0 +1,66 @@
+/*!
+Tools for asking which polyominos can surround a 1x1 hole with copies of themselves.
+
+The main pieces are re-exported here:
+- shapes are [`FreePointList`]s, lists of [`FreePoint`]s on the square grid
+- [`enumerate_polyominos`] calls back with every fixed omino of a size
+- transforms rotate, mirror, translate and normalize ominos
+- [`has_arrangement_board`] decides whether rotated and translated copies of an omino
+  can surround the hole at (0,0), and [`arrangement_board`] also returns the copies.
+  [`check_searchable`] tells shapes too large to search apart with a [`ShapeError`]
+- [`arrangement_pieces`] asks the same of a set of different [`Piece`]s, each with a
+  limited or unlimited number of copies, and [`puzzle::Puzzle`] tiles a region with them
+- [`find_arrangement`] and [`find_arrangement_translation`] are the older stack based
+  searches, the latter allowing translations only, and `placements` is an experimental
+  search of a table of each omino's placements around the hole with no board
+- [`verify_arrangement`] checks a claimed arrangement independently of the searches,
+  and `bench` times the searches against each other
+- `search` runs the search over every omino of a size, as the `search` command does.
+  Each command of the binary is a function taking its arguments in the module it
+  drives, with what they share in `command`
+
+The modules `text`, `packed`, `svg` and `report` read and write ominos and results,
+`db` keeps results per free omino across runs, `stats` gathers per size statistics
+such as perimeters and symmetry classes, and
+`transfer` counts fixed ominoes of sizes too large to enumerate, and `sample` draws
+random ones to estimate how many of those can't surround the hole. `oeis` holds the
+known counts the enumerators are checked against.
+`lattice` asks the same surround question generically over a [`lattice::Lattice`],
+with polyiamonds in `iamond`, polyhexes in `hex` and polycubes in `cube` as the other
+lattices.
+*/
+
+pub mod assemble;
+pub mod bench;
+pub mod board;
+pub mod command;
+pub mod cube;
+pub mod db;
+pub mod hex;
+pub mod iamond;
+pub mod lattice;
+pub mod oeis;
+pub mod omino;
+pub mod packed;
+pub mod placements;
+pub mod puzzle;
+pub mod report;
+pub mod sample;
+pub mod search;
+pub mod stats;
+pub mod svg;
+pub mod text;
+pub mod transfer;
+
+pub use assemble::{
+  find_arrangement, find_arrangement_translation, mirror_x_axis, mirror_y_axis, rotate_180,
+  rotate_ccw, rotate_cw, rotate_omino, rotational_deduplicate, rotational_equivalence,
+  verify_arrangement, Edge,
+};
+pub use board::{
+  arrangement_board, arrangement_pieces, check_searchable, has_arrangement_board, Piece,
+};
+pub use omino::{
+  enumerate_class, enumerate_polyominos, normalize_omino, translate_omino, Dir, FreePoint,
+  FreePointList, OminoClass, Point, ShapeError,
+};
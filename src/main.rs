@@ -1,69 +1,80 @@
-#![allow(unused)]
-#![feature(is_sorted)]
-#![feature(impl_trait_in_fn_trait_return)]
-#![feature(array_methods)]
-mod assemble;
-mod board;
-mod omino;
+use std::{env, io, process};
 
-use assemble::find_arrangement_translation;
-use itertools::Itertools;
-use std::time::SystemTime;
-
-use crate::{
-  assemble::{find_arrangement, has_rotated_corner_arrangement, rotational_deduplicate},
-  board::has_arrangement_board,
-  omino::{enumerate_polyominos, FreePointList, Grid},
+use polyominoes::{
+  bench, command::CommandError, lattice, oeis, packed, puzzle, sample, search, svg, transfer,
 };
 
-fn main() {
-  // for i in 1..=10 {
-  //   println!("{}-ominoes: {}", i, enumerate_polyominos(i).len());
-  // }
-
-  let lim = 13;
-  // for i in 5..=6 {
-  for i in 1..=25 {
-    let start = SystemTime::now();
-    let mut untranslateable_ominos = vec![];
-    let mut num_ominos = 0;
-    let ominos = enumerate_polyominos(i, |pts| {
-      num_ominos += 1;
-      let fpl: FreePointList = pts.into_iter().map(|pt| pt.clone().into()).collect();
-      if !has_arrangement_board(&fpl) {
-        untranslateable_ominos.push(fpl);
-      }
-    });
-    // let corner_arrangements =
-    //   fpl_ominos.iter().filter(|omino| !has_rotated_corner_arrangement(omino)).collect_vec();
-    // dbg!(&corner_arrangements.iter().map(|fpl| Grid::from((**fpl).clone())).collect_vec());
-    // println!("{}-ominoes, count: {} corner_able: {}", i, num_ominos, corner_arrangements.len());
+/*
+The command line: each command's work is done by a function in the library, so this
+only picks it and turns its error into a message and an exit code, 2 with the usage
+for bad arguments and 1 otherwise.
+ */
 
-    // let untranslateable_ominos =
-    //   fpl_ominos.into_iter().filter(|omino| find_arrangement(omino).is_none()).collect_vec();
-    let end = SystemTime::now();
-    println!(
-      "{} ominoes, count: {} untranslateable: {}, took {} seconds per 100k ominos",
-      i,
-      num_ominos,
-      untranslateable_ominos.len(),
-      (end.duration_since(start).unwrap().as_secs_f64() / num_ominos as f64) * 100_000.0
-    );
-    if untranslateable_ominos.len() > 0 {
-      dbg!(rotational_deduplicate(&untranslateable_ominos));
+fn main() {
+  let args: Vec<String> = env::args().skip(1).collect();
+  let rest = args.get(1..).unwrap_or_default();
+  let out = &mut io::stdout().lock();
+  let result = match args.first().map(|s| s.as_str()) {
+    None => search::run(&[], out),
+    Some("search") => search::run(rest, out),
+    Some("check") => search::check_file(rest, out),
+    Some("enumerate") => packed::write_enumeration(rest),
+    Some("count") => transfer::run(rest, out),
+    Some("oeis") => oeis::run(rest, out),
+    Some("sample") => sample::run(rest, out),
+    Some("pieces") => puzzle::surround_ascii(rest, out),
+    Some("puzzle") => puzzle::solve_ascii(rest, out),
+    Some("svg") => svg::render_file(rest, out),
+    Some("bench") => bench::run(rest, out),
+    Some("lattice") => lattice::run(rest, out),
+    Some(other) => Err(CommandError::Usage(format!("unknown command {:?}", other))),
+  };
+  match result {
+    Ok(()) => {}
+    Err(CommandError::Usage(msg)) => usage_error(&msg),
+    Err(CommandError::Failed(msg)) => {
+      eprintln!("{}", msg);
+      process::exit(1);
     }
   }
 }
-//num ominos, fixed:
-/*
-1 | 1
-2 | 2
-3 | 6
-4 | 19
-5 | 63
-6 | 216
-7 | 760
-8 | 2725
-9 | 9910
-10 | 36446
-*/
+
+fn usage_error(msg: &str) -> ! {
+  eprintln!("{}", msg);
+  eprintln!("usage: polyominoes [COMMAND]");
+  eprintln!("  search [--from N] [--to N] [--report PATH] [--stats] [--db PATH] [--progress SECS]");
+  eprintln!("                                         run the surround search, by default for");
+  eprintln!("                                         sizes 1..=25 with no command given, with");
+  eprintln!("                                         --stats printing statistics per size and");
+  eprintln!("                                         --db reusing and storing results per shape,");
+  eprintln!("                                         and progress on stderr every SECS (10)");
+  eprintln!("  check [--unsurroundable] [FILE]        test ominos read from FILE or stdin");
+  eprintln!("  svg [--arrangement] [--columns N] [FILE]  draw ominos read from FILE or stdin");
+  eprintln!("  enumerate N OUT [--box WxH | --class NAME]");
+  eprintln!("                                         write all fixed N-ominoes to OUT, packed,");
+  eprintln!("                                         or only those fitting in the box, or in");
+  eprintln!("                                         the class: row-convex, column-convex,");
+  eprintln!("                                         convex, directed or tree");
+  eprintln!("  count [--to N]                         count fixed ominoes by transfer matrix,");
+  eprintln!("                                         to 30 by default and at most 32,");
+  eprintln!("                                         which takes 7 minutes");
+  eprintln!("  oeis [--to N] [--slow-to N]            check enumerated counts against the OEIS,");
+  eprintln!("                                         and slow_omino_enum's up to --slow-to");
+  eprintln!("  sample N [--count K] [--free] [--seed S] [--spacing STEPS] [--burn-in STEPS]");
+  eprintln!("                                         estimate the unsurroundable fraction of");
+  eprintln!("                                         N-ominoes from K random ones");
+  eprintln!("  pieces [--once] [--mirror] [--max K] [FILE]");
+  eprintln!("                                         surround the hole with the pieces read");
+  eprintln!("                                         from FILE or stdin, using at most K");
+  eprintln!("  puzzle REGION PIECES [--count] [--mirror] [--unlimited]");
+  eprintln!("                                         tile the region drawn in REGION with");
+  eprintln!("                                         the pieces read from PIECES");
+  eprintln!("  bench [--from N] [--to N] [--corpus K] [--search NAME] [--flamegraph PATH]");
+  eprintln!("                                         time the surround searches on K ominos");
+  eprintln!("                                         per size, optionally profiling them;");
+  eprintln!("                                         the experimental table search only");
+  eprintln!("                                         runs as --search table");
+  eprintln!("  lattice triangle|hex|cube [--from N] [--to N]");
+  eprintln!("                                         run the surround search on another lattice");
+  process::exit(2);
+}
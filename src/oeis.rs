@@ -0,0 +1,238 @@
+use std::{fmt, io::Write};
+
+use crate::{
+  command::{failed, usage, CommandError},
+  omino::{enumerate_polyominos, slow_omino_enum, FreePointList, MAX_ENUMERABLE_SIZE},
+  stats::symmetry_mask,
+};
+
+/*
+Known counts of polyominoes from the OEIS, indexed by size, with a(0) = 1 as the OEIS
+has it, to check the enumerators against.
+
+A fixed omino with k of the 8 symmetries of the square is one of 8 / k fixed ominos in
+its free class, so the free ominos are the sum over fixed ones of k / 8, and likewise
+the one sided ones, which can't be mirrored, are the sum of r / 4 for the r rotations
+that fix an omino.
+ */
+
+//fixed polyominoes
+pub const A001168: [u64; 31] = [
+  1,
+  1,
+  2,
+  6,
+  19,
+  63,
+  216,
+  760,
+  2725,
+  9910,
+  36446,
+  135268,
+  505861,
+  1903890,
+  7204874,
+  27394666,
+  104592937,
+  400795844,
+  1540820542,
+  5940738676,
+  22964779660,
+  88983512783,
+  345532572678,
+  1344372335524,
+  5239988770268,
+  20457802016011,
+  79992676367108,
+  313224032098244,
+  1228088671826973,
+  4820975409710116,
+  18946775782611174,
+];
+
+//free polyominoes
+pub const A000105: [u64; 29] = [
+  1,
+  1,
+  1,
+  2,
+  5,
+  12,
+  35,
+  108,
+  369,
+  1285,
+  4655,
+  17073,
+  63600,
+  238591,
+  901971,
+  3426576,
+  13079255,
+  50107909,
+  192622052,
+  742624232,
+  2870671950,
+  11123060678,
+  43191857688,
+  168047007728,
+  654999700403,
+  2557227044764,
+  9999088822075,
+  39153010938487,
+  153511100594603,
+];
+
+//one sided polyominoes
+pub const A000988: [u64; 29] = [
+  1,
+  1,
+  1,
+  2,
+  7,
+  18,
+  60,
+  196,
+  704,
+  2500,
+  9189,
+  33896,
+  126759,
+  476270,
+  1802312,
+  6849777,
+  26152418,
+  100203194,
+  385221143,
+  1485200848,
+  5741256764,
+  22245940545,
+  86383382827,
+  336093325058,
+  1309998125640,
+  5114451441106,
+  19998172734786,
+  78306011677182,
+  307022182222506,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Counts {
+  pub fixed: u64,
+  pub free: u64,
+  pub one_sided: u64,
+}
+
+pub fn enumerated_counts(size: u8) -> Counts {
+  //counts the ominos of a size by enumerating the fixed ones
+  let (mut fixed, mut free_eighths, mut one_sided_quarters) = (0, 0, 0);
+  enumerate_polyominos(size, |pts| {
+    let omino: FreePointList = pts.iter().map(|&pt| pt.into()).collect();
+    let mask = symmetry_mask(&omino);
+    fixed += 1;
+    free_eighths += u64::from(mask.count_ones());
+    one_sided_quarters += u64::from((mask & 0b1111).count_ones());
+  });
+  Counts { fixed, free: free_eighths / 8, one_sided: one_sided_quarters / 4 }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+  pub sequence: &'static str,
+  pub size: u8,
+  pub expected: u64,
+  pub found: u64,
+  //what produced the wrong count
+  pub source: &'static str,
+}
+
+impl fmt::Display for Mismatch {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "{} gives {} for size {} of {}, but the OEIS has {}",
+      self.source, self.found, self.size, self.sequence, self.expected
+    )
+  }
+}
+
+impl std::error::Error for Mismatch {}
+
+pub fn check_size(size: u8, slow: bool) -> Result<Counts, Mismatch> {
+  /* checks the enumerated counts of one size against the tables, and with slow also
+  slow_omino_enum's count of fixed ominos, returning the first mismatch */
+  let index = usize::from(size);
+  let counts = enumerated_counts(size);
+  let mut found = vec![
+    ("A001168", A001168.get(index), counts.fixed, "enumerate_polyominos"),
+    ("A000105", A000105.get(index), counts.free, "enumerate_polyominos"),
+    ("A000988", A000988.get(index), counts.one_sided, "enumerate_polyominos"),
+  ];
+  if slow {
+    found.push((
+      "A001168",
+      A001168.get(index),
+      slow_omino_enum(size).len() as u64,
+      "slow_omino_enum",
+    ));
+  }
+  for (sequence, expected, found, source) in found {
+    if let Some(&expected) = expected {
+      if expected != found {
+        return Err(Mismatch { sequence, size, expected, found, source });
+      }
+    }
+  }
+  Ok(counts)
+}
+
+pub fn run(args: &[String], out: &mut impl Write) -> Result<(), CommandError> {
+  /* enumerates every size up to --to, checking the fixed, free and one sided counts
+  against the OEIS, and stops with an error at the first mismatch */
+  let mut to = 12;
+  let mut slow_to = 8;
+  let mut args = args.iter();
+  while let Some(arg) = args.next() {
+    let Some(size) = args.next().and_then(|n| n.parse().ok()) else {
+      return Err(usage(format!("{} needs a size", arg)));
+    };
+    match arg.as_str() {
+      "--to" => to = size,
+      "--slow-to" => slow_to = size,
+      _ => return Err(usage(format!("unknown oeis option {:?}", arg))),
+    }
+  }
+  if to > MAX_ENUMERABLE_SIZE {
+    return Err(usage(format!("can only enumerate up to size {}", MAX_ENUMERABLE_SIZE)));
+  }
+  writeln!(out, "{:>4} {:>14} {:>14} {:>14}", "size", "fixed", "free", "one sided")?;
+  for size in 1..=to {
+    let counts =
+      check_size(size, size <= slow_to).map_err(|e| failed(format!("MISMATCH: {}", e)))?;
+    writeln!(out, "{:>4} {:>14} {:>14} {:>14}", size, counts.fixed, counts.free, counts.one_sided)?;
+  }
+  writeln!(out, "all counts match")?;
+  Ok(())
+}
+
+#[cfg(test)]
+pub mod test {
+  use super::*;
+
+  #[test]
+  fn tables_agree_with_enumeration() {
+    for size in 1..=9 {
+      check_size(size, size <= 7).unwrap();
+    }
+  }
+
+  #[test]
+  fn tables_are_consistent() {
+    //each free class is 1 or 2 one sided classes, and 1 to 8 fixed ones
+    for n in 1..A000105.len() {
+      assert!(A000105[n] <= A000988[n] && A000988[n] <= 2 * A000105[n], "size {}", n);
+      assert!(A000988[n] <= A001168[n] && A001168[n] <= 8 * A000105[n], "size {}", n);
+    }
+  }
+}
@@ -1,14 +1,12 @@
-#![allow(unused)]
-
-use std::{cmp::Ordering, collections::HashSet, fmt::Write, fs::File};
+use std::{cmp::Ordering, collections::HashSet, fmt::Write};
 
 use itertools::Itertools;
 use smallvec::{smallvec, SmallVec};
 use std::fmt::Debug;
 
-use pprof::Report;
-
-const GRID_SIZE: usize = 17;
+const GRID_SIZE: usize = 25;
+//the grid is GRID_SIZE rows high, so larger ominos, eg a straight line, don't fit on it
+pub const MAX_ENUMERABLE_SIZE: u8 = GRID_SIZE as u8;
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
 pub struct Point {
@@ -17,7 +15,7 @@ pub struct Point {
 }
 
 #[derive(PartialEq, Eq, Clone, Copy, Hash)]
-pub enum TileState {
+pub(crate) enum TileState {
   Border,
   Occupied,
   Reachable,
@@ -26,7 +24,7 @@ pub enum TileState {
 use TileState::*;
 
 impl TileState {
-  pub fn to_char(&self) -> char {
+  pub fn to_char(self) -> char {
     match self {
       Border => 'B',
       Occupied => '#',
@@ -42,7 +40,7 @@ impl Debug for TileState {
 }
 
 #[derive(Clone, PartialEq, Eq, Hash)]
-pub struct Grid {
+pub(crate) struct Grid {
   grid: [[TileState; GRID_SIZE]; 2 * GRID_SIZE - 1],
 }
 
@@ -50,8 +48,8 @@ impl Default for Grid {
   fn default() -> Self {
     let basic_row: [TileState; GRID_SIZE] = [Free; GRID_SIZE];
     let mut grid = [basic_row; 2 * GRID_SIZE - 1];
-    for x in (0..GRID_SIZE - 1) {
-      grid[x][0] = TileState::Border;
+    for row in grid.iter_mut().take(GRID_SIZE - 1) {
+      row[0] = TileState::Border;
     }
 
     Self { grid }
@@ -64,19 +62,19 @@ impl Debug for Grid {
     impl Debug for Row {
       fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for ts in self.0 {
-          f.write_char(ts.to_char());
+          f.write_char(ts.to_char())?;
         }
         Ok(())
       }
     }
-    f.debug_struct("Grid").field("grid", &self.grid.map(|x| Row(x))).finish()
+    f.debug_struct("Grid").field("grid", &self.grid.map(Row)).finish()
   }
 }
 
 impl Grid {
   pub fn get_pos(&self, p: Point) -> TileState {
     let x_raw = p.x + ((GRID_SIZE - 1) as i8);
-    return self.grid[x_raw as usize][p.y as usize];
+    self.grid[x_raw as usize][p.y as usize]
   }
 
   pub fn set_pos(&mut self, p: Point, new_val: TileState) {
@@ -95,25 +93,31 @@ impl Grid {
     if p.x < (GRID_SIZE - 1) as i8 {
       out.push(Point { x: p.x + 1, y: p.y });
     }
-    if p.x > -1 * ((GRID_SIZE - 1) as i8) {
+    if p.x > -((GRID_SIZE - 1) as i8) {
       out.push(Point { x: p.x - 1, y: p.y })
     }
-    return out;
+    out
   }
 }
 
 pub type PointList = SmallVec<[Point; 16]>;
 
 fn enumerate_recursion(
-  cb: &mut impl FnMut(&[Point]) -> (),
+  cb: &mut impl FnMut(&[Point]),
   grid: &mut Grid,
   mut untried_set: PointList,
   occupied_set: &mut PointList,
   mut cur_omino_size: u8,
   size: u8,
+  class: OminoClass,
 ) {
   assert!(cur_omino_size <= size);
   while let Some(next_tile) = untried_set.pop() {
+    if class != OminoClass::All && !class.could_grow(grid, next_tile, occupied_set, &untried_set) {
+      //every omino grown from here is outside the class, so skip next_tile like a finished one
+      grid.set_pos(next_tile, Reachable);
+      continue;
+    }
     grid.set_pos(next_tile, Occupied);
     //dbg!("after set:", &grid);
     occupied_set.push(next_tile);
@@ -121,7 +125,9 @@ fn enumerate_recursion(
 
     if cur_omino_size == size {
       //we have produced an omino of the desired size
-      cb(occupied_set);
+      if class == OminoClass::All || class.contains_on(grid, occupied_set) {
+        cb(occupied_set);
+      }
     } else {
       //we aren't done with this omino yet, so we need to update reachability and so on
       let mut new_reachable_set = untried_set.clone();
@@ -134,7 +140,7 @@ fn enumerate_recursion(
         new_reachable_set.push(neighbor);
         grid.set_pos(neighbor, Reachable);
       }
-      enumerate_recursion(cb, grid, new_reachable_set, occupied_set, cur_omino_size, size);
+      enumerate_recursion(cb, grid, new_reachable_set, occupied_set, cur_omino_size, size, class);
       for neighbor in free_neighbors {
         grid.set_pos(neighbor, Free);
       }
@@ -147,12 +153,18 @@ fn enumerate_recursion(
   }
 }
 
-pub fn enumerate_polyominos(size: u8, mut cb: impl FnMut(&[Point]) -> ()) {
+pub fn enumerate_polyominos(size: u8, cb: impl FnMut(&[Point])) {
+  enumerate_class(size, OminoClass::All, cb)
+}
+
+pub fn enumerate_class(size: u8, class: OminoClass, mut cb: impl FnMut(&[Point])) {
+  //calls cb with every fixed omino of the given size in class
+  assert!(size <= MAX_ENUMERABLE_SIZE, "can enumerate up to size {}", MAX_ENUMERABLE_SIZE);
   let mut enum_grid = Grid::default();
-  let mut reachable_set: PointList = smallvec![Point { x: 0, y: 0 }];
+  let reachable_set: PointList = smallvec![Point { x: 0, y: 0 }];
   enum_grid.set_pos(Point { x: 0, y: 0 }, TileState::Reachable);
   let mut occupied_set: PointList = smallvec![];
-  let mut cur_omino_size: u8 = 0;
+  let cur_omino_size: u8 = 0;
 
   enumerate_recursion(
     &mut cb,
@@ -161,9 +173,201 @@ pub fn enumerate_polyominos(size: u8, mut cb: impl FnMut(&[Point]) -> ()) {
     &mut occupied_set,
     cur_omino_size,
     size,
+    class,
   );
 }
 
+/*
+Restricted classes of ominos. An omino is row convex if every row of it is one unbroken
+run of squares, column convex likewise for columns, and convex if both. It is directed
+if every square can be reached from the first one, (0, 0), by steps north and east
+inside it, and a tree if the graph of its squares and their shared edges is a tree,
+ie it has no 2x2 block or larger cycle. `FitsIn` isn't a class in the same sense, but
+is the ominos which fit in a box, such as the pieces for a physical puzzle.
+
+Enumerating a class prunes the search where no omino grown from the current one can
+be in it. The search never adds a square it has already passed over, so a gap that
+includes one of those squares is permanent, as is a cycle or a square outside the box.
+ */
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum OminoClass {
+  All,
+  RowConvex,
+  ColumnConvex,
+  Convex,
+  Directed,
+  Tree,
+  //fits in a box of at most width columns and height rows, without rotating
+  FitsIn { width: u8, height: u8 },
+}
+
+impl OminoClass {
+  //the names the CLI's --class takes, for every class but FitsIn, which --box gives
+  pub const NAMES: [(&'static str, OminoClass); 6] = [
+    ("all", OminoClass::All),
+    ("row-convex", OminoClass::RowConvex),
+    ("column-convex", OminoClass::ColumnConvex),
+    ("convex", OminoClass::Convex),
+    ("directed", OminoClass::Directed),
+    ("tree", OminoClass::Tree),
+  ];
+
+  pub fn from_name(name: &str) -> Option<Self> {
+    OminoClass::NAMES.iter().find(|&&(n, _)| n == name).map(|&(_, class)| class)
+  }
+
+  pub fn contains(self, omino: &[FreePoint]) -> bool {
+    let cells: HashSet<FreePoint> = omino.iter().copied().collect();
+    //whether the squares in line with start in the direction of step are one run
+    let is_run = |start: FreePoint, step: FreePoint| {
+      let mut run = 1;
+      for step in [step, invert_point(step)] {
+        let mut pt = sum_points(start, step);
+        while cells.contains(&pt) {
+          run += 1;
+          pt = sum_points(pt, step);
+        }
+      }
+      let line = omino.iter().filter(|p| (p.x - start.x) * step.y == (p.y - start.y) * step.x);
+      run == line.count()
+    };
+    let row_convex = || omino.iter().all(|&pt| is_run(pt, FreePoint { x: 1, y: 0 }));
+    let column_convex = || omino.iter().all(|&pt| is_run(pt, FreePoint { x: 0, y: 1 }));
+    match self {
+      OminoClass::All => true,
+      OminoClass::RowConvex => row_convex(),
+      OminoClass::ColumnConvex => column_convex(),
+      OminoClass::Convex => row_convex() && column_convex(),
+      OminoClass::Directed => {
+        let Some(&root) = omino.iter().min_by(|p, q| compare_points(p, q)) else { return false };
+        let mut seen = HashSet::from([root]);
+        let mut todo = vec![root];
+        while let Some(pt) = todo.pop() {
+          for next in [offset_in_dir(pt, N), offset_in_dir(pt, E)] {
+            if cells.contains(&next) && seen.insert(next) {
+              todo.push(next);
+            }
+          }
+        }
+        seen.len() == cells.len()
+      }
+      OminoClass::Tree => {
+        let edges: usize = omino
+          .iter()
+          .map(|&pt| [E, N].iter().filter(|&&d| cells.contains(&offset_in_dir(pt, d))).count())
+          .sum();
+        edges + 1 == omino.len() && is_connected(omino)
+      }
+      OminoClass::FitsIn { width, height } => {
+        let extent = |coord: fn(&FreePoint) -> i8| {
+          let (min, max) = omino.iter().map(coord).minmax().into_option().unwrap_or((0, -1));
+          (i16::from(max) - i16::from(min) + 1) as u16
+        };
+        extent(|pt| pt.x) <= u16::from(width) && extent(|pt| pt.y) <= u16::from(height)
+      }
+    }
+  }
+
+  fn contains_on(self, grid: &Grid, omino: &[Point]) -> bool {
+    /* contains for the occupied squares of the enumeration grid, which is much faster.
+    A row is one run if only one of its squares has no square to its west */
+    let occupied = |x: i8, y: u8| grid.get_pos(Point { x, y }) == Occupied;
+    let mut row_starts = [0u8; GRID_SIZE];
+    let mut column_starts = [0u8; 2 * GRID_SIZE - 1];
+    for &Point { x, y } in omino {
+      if x == -((GRID_SIZE - 1) as i8) || !occupied(x - 1, y) {
+        row_starts[y as usize] += 1;
+      }
+      if y == 0 || !occupied(x, y - 1) {
+        column_starts[(x + (GRID_SIZE - 1) as i8) as usize] += 1;
+      }
+    }
+    let row_convex = || row_starts.iter().all(|&starts| starts <= 1);
+    let column_convex = || column_starts.iter().all(|&starts| starts <= 1);
+    match self {
+      OminoClass::All | OminoClass::Tree | OminoClass::FitsIn { .. } => true,
+      OminoClass::RowConvex => row_convex(),
+      OminoClass::ColumnConvex => column_convex(),
+      OminoClass::Convex => row_convex() && column_convex(),
+      OminoClass::Directed => {
+        //every square reachable from the root is found by visiting squares in order of x + y
+        let mut by_distance: PointList = omino.into();
+        by_distance.sort_by_key(|&Point { x, y }| i16::from(x) + i16::from(y));
+        let mut reached: PointList = smallvec![];
+        for pt in by_distance {
+          let Point { x, y } = pt;
+          let from_south = y > 0 && reached.contains(&Point { x, y: y - 1 });
+          let from_west = reached.contains(&Point { x: x - 1, y });
+          if pt == (Point { x: 0, y: 0 }) || from_south || from_west {
+            reached.push(pt);
+          }
+        }
+        reached.len() == omino.len()
+      }
+    }
+  }
+
+  fn could_grow(
+    self,
+    grid: &Grid,
+    tile: Point,
+    occupied_set: &[Point],
+    untried_set: &[Point],
+  ) -> bool {
+    /* false if no omino grown by adding tile to occupied_set is in the class. Reachable
+    squares of grid not in untried_set have been passed over for good. This is exact for
+    trees and boxes, so contains_on needn't check them */
+    let can_fill = |x: i8, y: u8| match grid.get_pos(Point { x, y }) {
+      Occupied | Free => true,
+      Reachable => untried_set.contains(&Point { x, y }),
+      Border => false,
+    };
+    //whether a square that can't be filled lies between tile and an occupied square in a line
+    let gap_in = |line: &mut dyn Iterator<Item = (i8, u8)>| {
+      let mut blocked = false;
+      for (x, y) in line {
+        if grid.get_pos(Point { x, y }) == Occupied {
+          return blocked;
+        }
+        blocked |= !can_fill(x, y);
+      }
+      false
+    };
+    let max = (GRID_SIZE - 1) as i8;
+    let Point { x, y } = tile;
+    let row_gap =
+      || gap_in(&mut (-max..x).rev().map(|x| (x, y))) || gap_in(&mut (x + 1..=max).map(|x| (x, y)));
+    let column_gap = || {
+      gap_in(&mut (0..y).rev().map(|y| (x, y))) || gap_in(&mut (y + 1..=max as u8).map(|y| (x, y)))
+    };
+    match self {
+      OminoClass::All => true,
+      OminoClass::RowConvex => !row_gap(),
+      OminoClass::ColumnConvex => !column_gap(),
+      OminoClass::Convex => !row_gap() && !column_gap(),
+      OminoClass::Directed => {
+        //squares are never west of the root, and others need a way in from the south or west
+        let is_root = x == 0 && y == 0;
+        let south = y > 0 && can_fill(x, y - 1);
+        let west = x > 0 && can_fill(x - 1, y);
+        x >= 0 && (is_root || south || west)
+      }
+      OminoClass::Tree => {
+        let occupied =
+          Grid::get_neighbors(tile).into_iter().filter(|&n| grid.get_pos(n) == Occupied);
+        occupied.count() <= 1
+      }
+      OminoClass::FitsIn { width, height } => {
+        //rows start at 0, so the height is the highest row
+        let (min_x, max_x) =
+          occupied_set.iter().fold((x, x), |(min, max), pt| (min.min(pt.x), max.max(pt.x)));
+        ((max_x - min_x) as u8) < width && y < height
+      }
+    }
+  }
+}
+
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
 pub enum Dir {
   N,
@@ -204,43 +408,91 @@ pub struct FreePoint {
 
 pub type FreePointList = SmallVec<[FreePoint; 16]>;
 
-impl From<FreePointList> for Grid {
-  fn from(point_list: FreePointList) -> Grid {
+impl std::fmt::Display for FreePoint {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{},{}", self.x, self.y)
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShapeError {
+  Empty,
+  //a point too far out to convert or search
+  PointOutOfRange(FreePoint),
+  //a shape whose bounding box is wider or taller than limit
+  TooLarge { width: usize, height: usize, limit: usize },
+}
+
+impl std::fmt::Display for ShapeError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      ShapeError::Empty => write!(f, "the shape has no points"),
+      ShapeError::PointOutOfRange(pt) => write!(f, "point {} is out of range", pt),
+      ShapeError::TooLarge { width, height, limit } => {
+        write!(f, "the shape is {}x{}, but can be at most {} wide and tall", width, height, limit)
+      }
+    }
+  }
+}
+
+impl std::error::Error for ShapeError {}
+
+pub fn bounding_box(omino: &[FreePoint]) -> Option<(usize, usize)> {
+  //the (width, height) of the omino's bounding box
+  let (min_x, max_x) = omino.iter().map(|pt| pt.x).minmax().into_option()?;
+  let (min_y, max_y) = omino.iter().map(|pt| pt.y).minmax().into_option()?;
+  let extent = |min: i8, max: i8| (i16::from(max) - i16::from(min)) as usize + 1;
+  Some((extent(min_x, max_x), extent(min_y, max_y)))
+}
+
+impl TryFrom<FreePointList> for Grid {
+  type Error = ShapeError;
+
+  fn try_from(point_list: FreePointList) -> Result<Grid, ShapeError> {
+    //puts the lowest point of the bottom row at (0,0), the first square the enumeration adds
+    let (width, height) = bounding_box(&point_list).ok_or(ShapeError::Empty)?;
     let left_bottom = point_list
       .iter()
       .min_by(|FreePoint { x: x1, y: y1 }, FreePoint { x: x2, y: y2 }| (y1, x1).cmp(&(y2, x2)))
       .unwrap();
-    let translated_omino = translate_omino(&point_list, invert_point(*left_bottom));
+    let too_large = ShapeError::TooLarge { width, height, limit: GRID_SIZE };
     let mut out = Grid::default();
-    for FreePoint { x, y } in translated_omino {
-      let point = Point { x, y: y.try_into().unwrap() };
-      out.set_pos(point, Occupied);
+    for pt in &point_list {
+      let x = i16::from(pt.x) - i16::from(left_bottom.x);
+      let y = i16::from(pt.y) - i16::from(left_bottom.y);
+      if x.unsigned_abs() >= GRID_SIZE as u16 || y >= GRID_SIZE as i16 {
+        return Err(too_large);
+      }
+      out.set_pos(Point { x: x as i8, y: y as u8 }, Occupied);
     }
-    out
+    Ok(out)
   }
 }
 
 impl From<Point> for FreePoint {
   fn from(Point { x, y }: Point) -> Self {
-    FreePoint { x, y: y.try_into().unwrap() }
+    //points are made on the grid, or from FreePoints with y >= 0, so y always fits
+    FreePoint { x, y: y as i8 }
   }
 }
 
-impl From<FreePoint> for Point {
-  fn from(FreePoint { x, y }: FreePoint) -> Self {
-    Point { x, y: y.try_into().unwrap() }
+impl TryFrom<FreePoint> for Point {
+  type Error = ShapeError;
+
+  fn try_from(pt: FreePoint) -> Result<Self, ShapeError> {
+    match u8::try_from(pt.y) {
+      Ok(y) => Ok(Point { x: pt.x, y }),
+      Err(_) => Err(ShapeError::PointOutOfRange(pt)),
+    }
   }
 }
 
 impl FreePoint {
   pub fn get_neighbors(&self) -> FreePointList {
     let mut out = smallvec![];
-    match self {
-      FreePoint { x, y } => {
-        for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
-          out.push(FreePoint { x: x + dx, y: y + dy });
-        }
-      }
+    let FreePoint { x, y } = self;
+    for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+      out.push(FreePoint { x: x + dx, y: y + dy });
     }
     out
   }
@@ -289,7 +541,7 @@ pub fn translate_omino_iter(
 }
 
 pub fn invert_point(FreePoint { x, y }: FreePoint) -> FreePoint {
-  FreePoint { x: -1 * x, y: -1 * y }
+  FreePoint { x: -x, y: -y }
 }
 
 pub fn normalize_omino(omino: FreePointList) -> FreePointList {
@@ -304,6 +556,22 @@ pub fn normalize_omino(omino: FreePointList) -> FreePointList {
   translated_omino
 }
 
+pub fn is_connected(omino: &[FreePoint]) -> bool {
+  //flood fill from the first point, the omino is connected iff we reach every point
+  let Some(&start) = omino.first() else { return false };
+  let pts: HashSet<FreePoint> = omino.iter().copied().collect();
+  let mut seen = HashSet::from([start]);
+  let mut todo = vec![start];
+  while let Some(pt) = todo.pop() {
+    for neighbor in pt.get_neighbors() {
+      if pts.contains(&neighbor) && seen.insert(neighbor) {
+        todo.push(neighbor);
+      }
+    }
+  }
+  seen.len() == pts.len()
+}
+
 pub fn slow_omino_enum(size: u8) -> Vec<FreePointList> {
   if size == 1 {
     return vec![smallvec![FreePoint { x: 0, y: 0 }]];
@@ -323,9 +591,95 @@ pub fn slow_omino_enum(size: u8) -> Vec<FreePointList> {
   out.into_iter().collect()
 }
 
+#[cfg(test)]
 pub mod test {
   use super::*;
 
+  #[test]
+  fn fits_in_box() {
+    for (width, height) in [(1, 5), (2, 2), (3, 2), (3, 3), (4, 5)] {
+      let class = OminoClass::FitsIn { width, height };
+      for size in 1..=8 {
+        let mut pruned = vec![];
+        enumerate_class(size, class, |pts| {
+          pruned.push(pts.iter().map(|&pt| FreePoint::from(pt)).collect::<FreePointList>())
+        });
+        let mut filtered = 0;
+        enumerate_polyominos(size, |pts| {
+          let fpl: FreePointList = pts.iter().map(|&pt| pt.into()).collect();
+          filtered += usize::from(class.contains(&fpl));
+        });
+        assert!(pruned.iter().all(|omino| class.contains(omino)));
+        assert_eq!(pruned.len(), filtered, "{}x{} size {}", width, height, size);
+      }
+    }
+    //the pentominoes in a 3x3 box, from the bounding boxes 2x3, 3x2 and 3x3
+    let mut count = 0;
+    enumerate_class(5, OminoClass::FitsIn { width: 3, height: 3 }, |_| count += 1);
+    assert_eq!(count, 37);
+    //the straight lines reach the edges of the grid, up to the largest size
+    for size in [17, 18, MAX_ENUMERABLE_SIZE] {
+      for (width, height) in [(1, size), (size, 1)] {
+        let mut lines = 0;
+        enumerate_class(size, OminoClass::FitsIn { width, height }, |_| lines += 1);
+        assert_eq!(lines, 1, "{}x{}", width, height);
+      }
+    }
+  }
+
+  #[test]
+  fn class_counts() {
+    /* fixed ominos in each class, OEIS A001169 for row and column convex, A067675 for
+    convex, A005773 for directed and A066158 for trees, checked against filtering
+    every omino too */
+    let expected = [
+      (OminoClass::RowConvex, [1, 2, 6, 19, 61, 196, 629, 2017, 6466, 20727]),
+      (OminoClass::ColumnConvex, [1, 2, 6, 19, 61, 196, 629, 2017, 6466, 20727]),
+      (OminoClass::Convex, [1, 2, 6, 19, 59, 176, 502, 1374, 3630, 9312]),
+      (OminoClass::Directed, [1, 2, 5, 13, 35, 96, 267, 750, 2123, 6046]),
+      (OminoClass::Tree, [1, 2, 6, 18, 55, 174, 570, 1908, 6473, 22202]),
+    ];
+    for (name, class) in OminoClass::NAMES {
+      assert_eq!(OminoClass::from_name(name), Some(class));
+    }
+    assert_eq!(OminoClass::from_name("box"), None);
+    for (class, counts) in expected {
+      for (size, &count) in (1..).zip(counts.iter()) {
+        let mut pruned = 0;
+        enumerate_class(size, class, |_| pruned += 1);
+        assert_eq!(pruned, count, "{:?} size {}", class, size);
+        if size <= 8 {
+          let mut filtered = 0;
+          enumerate_polyominos(size, |pts| {
+            let fpl: FreePointList = pts.iter().map(|&pt| pt.into()).collect();
+            filtered += usize::from(class.contains(&fpl));
+          });
+          assert_eq!(filtered, count, "{:?} size {}", class, size);
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn fallible_conversions() {
+    let pt = FreePoint { x: -3, y: 5 };
+    assert_eq!(FreePoint::from(Point::try_from(pt).unwrap()), pt);
+    let below = FreePoint { x: 0, y: -1 };
+    assert_eq!(Point::try_from(below), Err(ShapeError::PointOutOfRange(below)));
+
+    let line = |len: i8| (0..len).map(|x| FreePoint { x, y: 0 }).collect::<FreePointList>();
+    assert!(Grid::try_from(line(25)).is_ok());
+    let too_large = ShapeError::TooLarge { width: 26, height: 1, limit: GRID_SIZE };
+    assert_eq!(Grid::try_from(line(26)), Err(too_large));
+    let column: FreePointList = (0..26).map(|y| FreePoint { x: 0, y }).collect();
+    assert!(matches!(Grid::try_from(column), Err(ShapeError::TooLarge { height: 26, .. })));
+    assert_eq!(Grid::try_from(FreePointList::new()), Err(ShapeError::Empty));
+    //the points furthest apart on i8s still give an error
+    let corners = smallvec![FreePoint { x: -128, y: -128 }, FreePoint { x: 127, y: 127 }];
+    assert_eq!(bounding_box(&corners), Some((256, 256)));
+    assert!(Grid::try_from(corners).is_err());
+  }
+
   #[test]
   fn neighbors_correct() {
     let mut ans: PointList = smallvec![
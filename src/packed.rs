@@ -0,0 +1,194 @@
+use std::{
+  fs::File,
+  io::{self, BufWriter, Read, Write},
+};
+
+use crate::{
+  command::{failed, usage, CommandError},
+  omino::{enumerate_class, FreePoint, FreePointList, OminoClass, MAX_ENUMERABLE_SIZE},
+};
+
+/*
+A compact binary format for big sets of ominos, so an enumeration can be done once,
+written to disk, and then re-filtered without enumerating again.
+
+A file is the 4 byte magic "PLYO" and a version byte, then one record per omino:
+the width and height of its bounding box as a byte each, then the cells of the
+bounding box as a bitmap, row by row from the bottom row up and left to right
+within a row, packed 8 cells to a byte (low bit first) and padded to a whole byte.
+An omino is stored up to translation, and is read back with the bottom left corner
+of its bounding box at (0,0). A 20-omino typically takes 8 bytes.
+ */
+
+pub const MAGIC: &[u8; 4] = b"PLYO";
+const VERSION: u8 = 1;
+
+fn invalid_data(msg: &str) -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+pub struct OminoWriter<W: Write> {
+  inner: W,
+  buf: Vec<u8>,
+}
+
+impl<W: Write> OminoWriter<W> {
+  pub fn new(mut inner: W) -> io::Result<Self> {
+    inner.write_all(MAGIC)?;
+    inner.write_all(&[VERSION])?;
+    Ok(Self { inner, buf: vec![] })
+  }
+
+  pub fn write(&mut self, omino: &[FreePoint]) -> io::Result<()> {
+    if omino.is_empty() {
+      return Err(io::Error::new(io::ErrorKind::InvalidInput, "can't store an empty omino"));
+    }
+    let min_x = omino.iter().map(|pt| i32::from(pt.x)).min().unwrap();
+    let max_x = omino.iter().map(|pt| i32::from(pt.x)).max().unwrap();
+    let min_y = omino.iter().map(|pt| i32::from(pt.y)).min().unwrap();
+    let max_y = omino.iter().map(|pt| i32::from(pt.y)).max().unwrap();
+    let (width, height) = (max_x - min_x + 1, max_y - min_y + 1);
+    if width > 128 || height > 128 {
+      return Err(io::Error::new(io::ErrorKind::InvalidInput, "omino is more than 128 wide"));
+    }
+    let (width, height) = (width as u8, height as u8);
+
+    self.buf.clear();
+    self.buf.resize(2 + (usize::from(width) * usize::from(height)).div_ceil(8), 0);
+    self.buf[0] = width;
+    self.buf[1] = height;
+    for pt in omino {
+      let bit = (i32::from(pt.y) - min_y) as usize * usize::from(width)
+        + (i32::from(pt.x) - min_x) as usize;
+      self.buf[2 + bit / 8] |= 1 << (bit % 8);
+    }
+    self.inner.write_all(&self.buf)
+  }
+
+  pub fn into_inner(mut self) -> io::Result<W> {
+    self.inner.flush()?;
+    Ok(self.inner)
+  }
+}
+
+pub struct OminoReader<R: Read> {
+  inner: R,
+  buf: Vec<u8>,
+}
+
+impl<R: Read> OminoReader<R> {
+  pub fn new(mut inner: R) -> io::Result<Self> {
+    let mut header = [0; 5];
+    inner.read_exact(&mut header)?;
+    if &header[..4] != MAGIC {
+      return Err(invalid_data("not a packed omino file"));
+    }
+    if header[4] != VERSION {
+      return Err(invalid_data("unsupported packed omino version"));
+    }
+    Ok(Self { inner, buf: vec![] })
+  }
+
+  fn read_omino(&mut self) -> io::Result<Option<FreePointList>> {
+    let mut dims = [0; 2];
+    //a clean end of file can only happen between records
+    match self.inner.read(&mut dims[..1])? {
+      0 => return Ok(None),
+      _ => self.inner.read_exact(&mut dims[1..])?,
+    }
+    let [width, height] = dims;
+    if width == 0 || height == 0 || width > 128 || height > 128 {
+      return Err(invalid_data("bad omino dimensions"));
+    }
+    let cells = usize::from(width) * usize::from(height);
+    self.buf.resize(cells.div_ceil(8), 0);
+    self.inner.read_exact(&mut self.buf)?;
+    let mut out = FreePointList::new();
+    for bit in 0..cells {
+      if self.buf[bit / 8] & (1 << (bit % 8)) != 0 {
+        let x = (bit % usize::from(width)) as i8;
+        let y = (bit / usize::from(width)) as i8;
+        out.push(FreePoint { x, y });
+      }
+    }
+    if out.is_empty() {
+      return Err(invalid_data("empty omino"));
+    }
+    Ok(Some(out))
+  }
+}
+
+impl<R: Read> Iterator for OminoReader<R> {
+  type Item = io::Result<FreePointList>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self.read_omino().transpose()
+  }
+}
+
+pub fn write_enumeration(args: &[String]) -> Result<(), CommandError> {
+  /* the enumerate command: writes every fixed n-omino to a file in this format, or with
+  --box WxH those that fit, or with --class NAME those in one of OminoClass::NAMES */
+  let (Some(size), Some(path)) = (args.first().and_then(|n| n.parse().ok()), args.get(1)) else {
+    return Err(usage("enumerate needs a size and an output file"));
+  };
+  if size > MAX_ENUMERABLE_SIZE {
+    return Err(usage(format!("can only enumerate up to size {}", MAX_ENUMERABLE_SIZE)));
+  }
+  let class = match &args[2..] {
+    [] => OminoClass::All,
+    [flag, dims] if flag == "--box" => {
+      let parsed = dims.split_once('x').and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)));
+      let Some((width, height)) = parsed else {
+        return Err(usage(format!("bad box {:?}, expected WIDTHxHEIGHT", dims)));
+      };
+      OminoClass::FitsIn { width, height }
+    }
+    [flag, name] if flag == "--class" => OminoClass::from_name(name).ok_or_else(|| {
+      let names = OminoClass::NAMES.map(|(name, _)| name).join(", ");
+      usage(format!("unknown class {:?}, expected one of {}", name, names))
+    })?,
+    _ => return Err(usage("enumerate only takes --box WxH or --class NAME after the output file")),
+  };
+  let could_not_write = |e: io::Error| failed(format!("could not write {}: {}", path, e));
+  let file = File::create(path).map_err(|e| failed(format!("could not create {}: {}", path, e)))?;
+  let mut writer = OminoWriter::new(BufWriter::new(file)).map_err(could_not_write)?;
+  let mut result = Ok(());
+  let mut count: u64 = 0;
+  enumerate_class(size, class, |pts| {
+    if result.is_ok() {
+      let fpl: FreePointList = pts.iter().map(|&pt| pt.into()).collect();
+      result = writer.write(&fpl);
+      count += 1;
+    }
+  });
+  result.and_then(|_| writer.into_inner().map(|_| ())).map_err(could_not_write)?;
+  eprintln!("wrote {} {}-ominoes to {}", count, size, path);
+  Ok(())
+}
+
+#[cfg(test)]
+pub mod test {
+  use super::*;
+  use crate::omino::{enumerate_polyominos, normalize_omino};
+
+  #[test]
+  fn packed_round_trip() {
+    let mut ominos: Vec<FreePointList> = vec![];
+    enumerate_polyominos(6, |pts| ominos.push(pts.iter().map(|&pt| pt.into()).collect()));
+    let mut writer = OminoWriter::new(vec![]).unwrap();
+    for omino in &ominos {
+      writer.write(omino).unwrap();
+    }
+    let bytes = writer.into_inner().unwrap();
+    let read: Vec<_> = OminoReader::new(&bytes[..]).unwrap().map(|o| o.unwrap()).collect();
+    assert_eq!(read.len(), ominos.len());
+    for (original, read) in ominos.into_iter().zip(read) {
+      //the reader puts the bounding box at the origin, which normalizing undoes
+      assert_eq!(normalize_omino(original), normalize_omino(read));
+    }
+    //a record cut off part way through is an error, not a silent end of file
+    let truncated = OminoReader::new(&bytes[..bytes.len() - 1]).unwrap();
+    assert!(truncated.last().unwrap().is_err());
+  }
+}
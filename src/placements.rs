@@ -0,0 +1,257 @@
+use itertools::Itertools;
+
+use crate::{
+  assemble::{iter_perimeter_on, rotate_omino, translation_of_a_to_b, Edge},
+  board::{Board, LARGE_SIZE, MAX_SIZE},
+  omino::{
+    normalize_omino, offset_in_dir, translate_omino, translate_omino_iter, Dir, FreePoint,
+    FreePointList,
+  },
+};
+use Dir::*;
+
+/*
+The board search works out the placements covering each side of the hole again at
+every node, by scanning every perimeter edge of every rotation for the ones facing
+the side, and then writes each copy to the board to see whether it overlaps the
+others. Here that work is done once per omino.
+
+A placement is a rotation and translation of the omino that covers a square next to
+the hole but not the hole itself. The table lists the placements covering each side
+of the hole, keyed by the side's direction from the hole, and holds a bitset per
+placement of the placements it doesn't overlap. An arrangement is then a set of at
+most 4 pairwise compatible placements covering all 4 sides, and the search only ands
+bitsets together: it covers the first uncovered side with each placement still
+compatible with everything chosen so far, narrowing the compatible set as it goes.
+
+This is experimental: it agrees with the board search but is slower than it on every
+corpus measured except a single unsurroundable cross (see notes.txt), as building the
+table costs more than the board search spends on most ominos. So nothing calls it but its tests and
+`bench --search table`, and the surround search uses the board.
+ */
+
+pub struct PlacementTable {
+  //the distinct rotations of the omino
+  rotations: Vec<FreePointList>,
+  //each placement's rotation and translation
+  placed: Vec<(usize, FreePoint)>,
+  //the sides of the hole each placement covers, bit d for each Dir d
+  pub sides: Vec<u8>,
+  //the placements covering the square in each Dir from the hole
+  by_dir: [Vec<usize>; 4],
+  //u64 words per bitset
+  words: usize,
+  //bit q of placement p's bitset is set iff p and q don't overlap
+  compatible: Vec<u64>,
+}
+
+impl PlacementTable {
+  pub fn new(omino: &FreePointList) -> Self {
+    let mut rotations: Vec<FreePointList> = vec![];
+    let mut normalized: Vec<FreePointList> = vec![];
+    for amt in 0..4 {
+      //a rotation the omino is symmetric under gives the same placements again
+      let rotated = rotate_omino(omino, amt);
+      let rotated_normalized = normalize_omino(rotated.clone());
+      if !normalized.contains(&rotated_normalized) {
+        rotations.push(rotated);
+        normalized.push(rotated_normalized);
+      }
+    }
+
+    let perimeters: Vec<_> = rotations
+      .iter()
+      .map(|rotated| {
+        if Board::<MAX_SIZE>::fits(rotated) {
+          iter_perimeter_on::<MAX_SIZE>(rotated)
+        } else {
+          iter_perimeter_on::<LARGE_SIZE>(rotated)
+        }
+      })
+      .collect();
+    let hole = FreePoint { x: 0, y: 0 };
+    let mut placed: Vec<(usize, FreePoint)> = vec![];
+    let mut sides = vec![];
+    let mut by_dir: [Vec<usize>; 4] = Default::default();
+    for dir in [N, E, S, W] {
+      let target = offset_in_dir(hole, dir);
+      for (r, (rotated, perimeter)) in rotations.iter().zip(&perimeters).enumerate() {
+        //a square whose edge facing back towards the hole is on the perimeter
+        for &Edge(pt, d) in perimeter {
+          if d != dir.flip() {
+            continue;
+          }
+          let translation = translation_of_a_to_b(pt, target);
+          //the square next to the hole in direction d, moved back onto the rotation
+          let covers = |d: Dir| {
+            let square = offset_in_dir(hole, d);
+            rotated.contains(&translation_of_a_to_b(translation, square))
+          };
+          //the square at target is covered, so only the other sides need checking
+          let covered = [N, E, S, W]
+            .into_iter()
+            .filter(|&other| other == dir || covers(other))
+            .fold(0, |m, d| m | 1 << d as u8);
+          //a copy covering an earlier side was listed for it already
+          let p = if covered & ((1 << dir as u8) - 1) != 0 {
+            placed.iter().position(|&key| key == (r, translation)).unwrap()
+          } else {
+            placed.push((r, translation));
+            sides.push(covered);
+            placed.len() - 1
+          };
+          by_dir[dir as usize].push(p);
+        }
+      }
+    }
+
+    //the placements covering each square of the box around them all, which overlap each other
+    let words = placed.len().div_ceil(64);
+    let squares =
+      |&(r, translation): &(usize, FreePoint)| translate_omino_iter(&rotations[r], translation);
+    let all = || placed.iter().flat_map(squares);
+    let (min_x, max_x) = all().map(|pt| pt.x).minmax().into_option().unwrap();
+    let (min_y, max_y) = all().map(|pt| pt.y).minmax().into_option().unwrap();
+    let width = (i16::from(max_x) - i16::from(min_x)) as usize + 1;
+    let height = (i16::from(max_y) - i16::from(min_y)) as usize + 1;
+    let square = |pt: FreePoint| {
+      let offset = |a: i8, min: i8| (i16::from(a) - i16::from(min)) as usize;
+      offset(pt.y, min_y) * width + offset(pt.x, min_x)
+    };
+    let mut covering = vec![0u64; width * height * words];
+    for (p, placement) in placed.iter().enumerate() {
+      for pt in squares(placement) {
+        covering[square(pt) * words + p / 64] |= 1 << (p % 64);
+      }
+    }
+    let mut compatible = vec![u64::MAX; words * placed.len()];
+    for (p, placement) in placed.iter().enumerate() {
+      let bitset = &mut compatible[p * words..(p + 1) * words];
+      for pt in squares(placement) {
+        let overlapping = &covering[square(pt) * words..(square(pt) + 1) * words];
+        for (word, overlapping) in bitset.iter_mut().zip(overlapping) {
+          *word &= !overlapping;
+        }
+      }
+    }
+    PlacementTable { rotations, placed, sides, by_dir, words, compatible }
+  }
+
+  pub fn len(&self) -> usize {
+    self.placed.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.placed.is_empty()
+  }
+
+  pub fn copy(&self, p: usize) -> FreePointList {
+    //the squares of placement p
+    let (r, translation) = self.placed[p];
+    translate_omino(&self.rotations[r], translation)
+  }
+
+  pub fn placements(&self, dir: Dir) -> &[usize] {
+    //the placements covering the square in direction dir from the hole
+    &self.by_dir[dir as usize]
+  }
+
+  pub fn compatible(&self, p: usize, q: usize) -> bool {
+    self.bitset(p)[q / 64] & (1 << (q % 64)) != 0
+  }
+
+  fn bitset(&self, p: usize) -> &[u64] {
+    &self.compatible[p * self.words..(p + 1) * self.words]
+  }
+
+  fn search(
+    &self,
+    covered: u8,
+    allowed: &[u64],
+    scratch: &mut [u64],
+    chosen: &mut Vec<usize>,
+  ) -> bool {
+    //chooses placements from allowed until every side is covered
+    if covered == 0b1111 {
+      return true;
+    }
+    let dir = covered.trailing_ones() as usize;
+    let (next, scratch) = scratch.split_at_mut(self.words);
+    for &p in &self.by_dir[dir] {
+      if allowed[p / 64] & (1 << (p % 64)) == 0 {
+        continue;
+      }
+      for (word, (a, b)) in next.iter_mut().zip(allowed.iter().zip(self.bitset(p))) {
+        *word = a & b;
+      }
+      chosen.push(p);
+      if self.search(covered | self.sides[p], next, scratch, chosen) {
+        return true;
+      }
+      chosen.pop();
+    }
+    false
+  }
+
+  pub fn arrangement(&self) -> Option<Vec<usize>> {
+    //pairwise compatible placements covering every side, each covering at least one more
+    let allowed = vec![u64::MAX; self.words];
+    //each placement covers another side, so at most 4 levels of bitsets are needed
+    let mut scratch = vec![0; 4 * self.words];
+    let mut chosen = vec![];
+    self.search(0, &allowed, &mut scratch, &mut chosen).then_some(chosen)
+  }
+}
+
+pub fn arrangement_table(omino: &FreePointList) -> Option<Vec<FreePointList>> {
+  //like arrangement_board, searching the omino's placement table
+  let table = PlacementTable::new(omino);
+  let chosen = table.arrangement()?;
+  Some(chosen.into_iter().map(|p| table.copy(p)).collect())
+}
+
+pub fn has_arrangement_table(omino: &FreePointList) -> bool {
+  PlacementTable::new(omino).arrangement().is_some()
+}
+
+#[cfg(test)]
+pub mod test {
+  use super::*;
+  use crate::{
+    assemble::verify_arrangement, board::arrangement_board, omino::enumerate_polyominos,
+    text::parse_ascii,
+  };
+
+  #[test]
+  fn table_placements() {
+    //the domino has 2 rotations, and 3 placements covering each side, all of them 1 side
+    let table = PlacementTable::new(&parse_ascii("##").unwrap());
+    assert_eq!(table.len(), 12);
+    for dir in [N, E, S, W] {
+      assert_eq!(table.placements(dir).len(), 3);
+      for &p in table.placements(dir) {
+        assert_eq!(table.sides[p], 1 << dir as u8);
+        assert!(!table.compatible(p, p));
+      }
+    }
+    let (n, e) = (table.placements(N), table.placements(E));
+    assert!(n.iter().any(|&p| e.iter().any(|&q| !table.compatible(p, q))));
+    assert_eq!(table.arrangement().map(|chosen| chosen.len()), Some(4));
+  }
+
+  #[test]
+  fn table_matches_board() {
+    for size in 1..=8 {
+      enumerate_polyominos(size, |pts| {
+        let omino: FreePointList = pts.iter().map(|&pt| pt.into()).collect();
+        let copies = arrangement_table(&omino);
+        assert_eq!(copies.is_some(), arrangement_board(&omino).is_some());
+        if let Some(copies) = copies {
+          assert!(verify_arrangement(&omino, &copies));
+        }
+      });
+    }
+    let cross = ".#####.\n...#...\n#..#..#\n#######\n#..#..#\n...#...\n.#####.";
+    assert!(!has_arrangement_table(&parse_ascii(cross).unwrap()));
+  }
+}
@@ -0,0 +1,338 @@
+use std::{
+  fmt,
+  io::{self, Write},
+};
+
+use crate::{
+  assemble::{mirror_y_axis, rotate_omino},
+  board::{arrangement_pieces, Board, Piece, MAX_SIZE},
+  command::{failed, read_ominos, usage, CommandError},
+  omino::{compare_points, normalize_omino, translate_omino_iter, FreePoint, FreePointList},
+  text::to_labelled_ascii,
+};
+
+/*
+Packing puzzles: tile a target region exactly with copies of a set of pieces, eg the 12
+pentominoes in a 6x10 rectangle, or in an 8x8 board with a 2x2 hole in the middle.
+
+The search fills the region's squares in order, bottom row first. The first empty
+square must be covered by the first square of whichever orientation of a piece covers
+it, so each node of the search tries each piece with copies left in each orientation
+in just one place. Squares outside the region start out occupied on the board, so a
+placement fits iff the board accepts it. Rows are kept short by transposing a region
+that is wider than it is tall, which makes dead ends show up sooner.
+ */
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum PuzzleError {
+  EmptyRegion,
+  //the region and pieces must fit on a board with room to spare
+  TooLarge,
+}
+
+impl fmt::Display for PuzzleError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      PuzzleError::EmptyRegion => write!(f, "the region has no squares"),
+      PuzzleError::TooLarge => write!(f, "the region and pieces are too large to search"),
+    }
+  }
+}
+
+impl std::error::Error for PuzzleError {}
+
+fn transpose(FreePoint { x, y }: FreePoint) -> FreePoint {
+  FreePoint { x: y, y: x }
+}
+
+pub struct Puzzle {
+  //the region's squares in the order they are filled
+  region: FreePointList,
+  //(piece, orientation) pairs, each orientation sorted so its first square comes first
+  orientations: Vec<(usize, FreePointList)>,
+  copies: Vec<Option<usize>>,
+  transposed: bool,
+}
+
+impl Puzzle {
+  pub fn new(region: &[FreePoint], pieces: &[Piece], mirror: bool) -> Result<Self, PuzzleError> {
+    /* a puzzle to tile region with rotated, and if mirror also reflected, copies of
+    the pieces, each used at most its number of copies */
+    if region.is_empty() {
+      return Err(PuzzleError::EmptyRegion);
+    }
+    let extent = |coord: fn(&FreePoint) -> i8| {
+      region.iter().map(coord).max().unwrap() - region.iter().map(coord).min().unwrap()
+    };
+    let transposed = extent(|pt| pt.x) > extent(|pt| pt.y);
+    let orient = |pt: FreePoint| if transposed { transpose(pt) } else { pt };
+    let mut region: FreePointList = region.iter().map(|&pt| orient(pt)).collect();
+    region.sort_by(compare_points);
+
+    //placements reach a piece's size beyond the region, and the board reaches OFFSET from (0,0)
+    let largest_piece = pieces.iter().map(|piece| piece.omino.len()).max().unwrap_or(0);
+    let furthest = region.iter().map(|pt| pt.x.unsigned_abs().max(pt.y.unsigned_abs())).max();
+    if usize::from(furthest.unwrap()) + largest_piece >= MAX_SIZE / 2 {
+      return Err(PuzzleError::TooLarge);
+    }
+
+    let mut orientations: Vec<(usize, FreePointList)> = vec![];
+    for (i, piece) in pieces.iter().enumerate() {
+      let omino: FreePointList = piece.omino.iter().map(|&pt| orient(pt)).collect();
+      let mirrored: FreePointList = omino.iter().map(|&pt| mirror_y_axis(pt)).collect();
+      let reflections = if mirror { vec![omino, mirrored] } else { vec![omino] };
+      for omino in &reflections {
+        for amt in 0..4 {
+          //normalizing puts the first square at (0,0), and sorting puts it first
+          let mut rotated = normalize_omino(rotate_omino(omino, amt));
+          rotated.sort_by(compare_points);
+          if !orientations.contains(&(i, rotated.clone())) {
+            orientations.push((i, rotated));
+          }
+        }
+      }
+    }
+    let copies = pieces.iter().map(|piece| piece.copies).collect();
+    Ok(Puzzle { region, orientations, copies, transposed })
+  }
+
+  fn search(
+    &self,
+    board: &mut Board,
+    remaining: &mut [Option<usize>],
+    start: usize,
+    placed: &mut Vec<(usize, FreePointList)>,
+    on_solution: &mut impl FnMut(&[(usize, FreePointList)]) -> bool,
+  ) -> bool {
+    //calls on_solution with each tiling until it returns true, returning whether it did
+    let Some(offset) = self.region[start..].iter().position(|&pt| !board.contains(pt)) else {
+      return on_solution(placed);
+    };
+    let start = start + offset;
+    let target = self.region[start];
+
+    for (piece, omino) in &self.orientations {
+      if remaining[*piece] == Some(0) {
+        continue;
+      }
+      let translation = FreePoint { x: target.x - omino[0].x, y: target.y - omino[0].y };
+      if board.add_(|| translate_omino_iter(omino, translation)) {
+        placed.push((*piece, translate_omino_iter(omino, translation).collect()));
+        if let Some(copies) = remaining[*piece].as_mut() {
+          *copies -= 1;
+        }
+        let stop = self.search(board, remaining, start + 1, placed, on_solution);
+        if let Some(copies) = remaining[*piece].as_mut() {
+          *copies += 1;
+        }
+        placed.pop();
+        board.undo_(|| translate_omino_iter(omino, translation));
+        if stop {
+          return true;
+        }
+      }
+    }
+    false
+  }
+
+  fn run(&self, mut on_solution: impl FnMut(Vec<(usize, FreePointList)>) -> bool) {
+    //everything off the region starts occupied
+    let mut board = Board([[true; MAX_SIZE]; MAX_SIZE]);
+    for &pt in &self.region {
+      *board.get_mut(pt) = false;
+    }
+    let mut remaining = self.copies.clone();
+    let mut placed = vec![];
+    self.search(&mut board, &mut remaining, 0, &mut placed, &mut |placed| {
+      //solutions are given in the region's original coordinates
+      let solution = placed
+        .iter()
+        .map(|(piece, copy)| {
+          let copy = copy.iter().map(|&pt| if self.transposed { transpose(pt) } else { pt });
+          (*piece, copy.collect())
+        })
+        .collect();
+      on_solution(solution)
+    });
+  }
+
+  pub fn solve(&self) -> Option<Vec<(usize, FreePointList)>> {
+    //one tiling, as the placed copies with the index of the piece each is a copy of
+    let mut solution = None;
+    self.run(|placed| {
+      solution = Some(placed);
+      true
+    });
+    solution
+  }
+
+  pub fn count(&self) -> u64 {
+    /* the number of tilings, counting ones that are rotations or reflections of each
+    other separately */
+    let mut count = 0;
+    self.run(|_| {
+      count += 1;
+      false
+    });
+    count
+  }
+}
+
+fn write_placed(placed: &[(usize, FreePointList)], out: &mut impl Write) -> io::Result<()> {
+  //the copies drawn with a letter each, and which piece each letter is a copy of
+  let copies: Vec<_> = placed.iter().map(|(_, copy)| copy.clone()).collect();
+  write!(out, "{}", to_labelled_ascii(&copies))?;
+  for (label, (piece, _)) in ('A'..='Z').zip(placed) {
+    writeln!(out, "{}: piece {}", label, piece + 1)?;
+  }
+  Ok(())
+}
+
+pub fn surround_ascii(args: &[String], out: &mut impl Write) -> Result<(), CommandError> {
+  /* the pieces command: tries to surround the hole with copies of the pieces read from
+  a file (or stdin), any number of each unless --once, rotated and with --mirror also
+  reflected */
+  let mut copies = None;
+  let mut mirror = false;
+  let mut max_placed = 4;
+  let mut path = None;
+  let mut args = args.iter();
+  while let Some(arg) = args.next() {
+    match arg.as_str() {
+      "--once" => copies = Some(1),
+      "--mirror" => mirror = true,
+      "--max" => match args.next().and_then(|n| n.parse().ok()) {
+        Some(n) => max_placed = n,
+        None => return Err(usage("--max needs a number")),
+      },
+      _ => path = Some(arg.as_str()),
+    }
+  }
+  let pieces = read_ominos(path)?
+    .map(|omino| Ok(Piece { omino: omino?, copies }))
+    .collect::<Result<Vec<_>, CommandError>>()?;
+  match arrangement_pieces(&pieces, mirror, max_placed) {
+    Some(placed) => write_placed(&placed, out)?,
+    None => writeln!(out, "no arrangement of at most {} pieces", max_placed)?,
+  }
+  Ok(())
+}
+
+pub fn solve_ascii(args: &[String], out: &mut impl Write) -> Result<(), CommandError> {
+  /* the puzzle command: tiles a region with the pieces, each used once unless
+  --unlimited, printing one tiling, or with --count the number of tilings */
+  let mut count = false;
+  let mut mirror = false;
+  let mut copies = Some(1);
+  let mut paths = vec![];
+  for arg in args {
+    match arg.as_str() {
+      "--count" => count = true,
+      "--mirror" => mirror = true,
+      "--unlimited" => copies = None,
+      _ => paths.push(arg.as_str()),
+    }
+  }
+  let [region_path, pieces_path] = paths[..] else {
+    return Err(usage("puzzle needs a region file and a pieces file"));
+  };
+  let Some(region) = read_ominos(Some(region_path))?.next() else {
+    return Err(failed(format!("no region in {}", region_path)));
+  };
+  let pieces = read_ominos(Some(pieces_path))?
+    .map(|omino| Ok(Piece { omino: omino?, copies }))
+    .collect::<Result<Vec<_>, CommandError>>()?;
+  let puzzle = Puzzle::new(&region?, &pieces, mirror).map_err(|e| failed(e.to_string()))?;
+  if count {
+    writeln!(out, "{} tilings", puzzle.count())?;
+  } else {
+    match puzzle.solve() {
+      Some(placed) => write_placed(&placed, out)?,
+      None => writeln!(out, "no tiling")?,
+    }
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+pub mod test {
+  use super::*;
+  use crate::text::parse_ominos;
+
+  pub fn pentominoes() -> Vec<Piece> {
+    let pentominoes = parse_ominos(
+      "#####\n\n##\n#.\n#.\n#.\n\n.#\n##\n#.\n#.\n\n##\n##\n#.\n\n###\n.#.\n.#.\n\n\
+      #.#\n###\n\n#..\n#..\n###\n\n#..\n##.\n.##\n\n.#.\n###\n.#.\n\n.#\n##\n.#\n.#\n\n\
+      ##.\n.#.\n.##\n\n.##\n##.\n.#.",
+    )
+    .unwrap();
+    pentominoes.into_iter().map(|omino| Piece { omino, copies: Some(1) }).collect()
+  }
+
+  fn rectangle(width: i8, height: i8) -> FreePointList {
+    (0..height).flat_map(|y| (0..width).map(move |x| FreePoint { x, y })).collect()
+  }
+
+  fn is_tiling(region: &[FreePoint], solution: &[(usize, FreePointList)]) -> bool {
+    let mut covered: Vec<FreePoint> = solution.iter().flat_map(|(_, copy)| copy.clone()).collect();
+    covered.sort();
+    let mut region = region.to_vec();
+    region.sort();
+    covered == region
+  }
+
+  #[test]
+  fn pentomino_rectangles() {
+    //3x20 has 2 tilings up to symmetry, so 8 in all, and 20x3 is the same puzzle transposed
+    for region in [rectangle(20, 3), rectangle(3, 20)] {
+      let puzzle = Puzzle::new(&region, &pentominoes(), true).unwrap();
+      assert_eq!(puzzle.count(), 8);
+      let solution = puzzle.solve().unwrap();
+      assert_eq!(solution.len(), 12);
+      assert!(is_tiling(&region, &solution));
+    }
+    //without reflections some pieces can't fit
+    let puzzle = Puzzle::new(&rectangle(20, 3), &pentominoes(), false).unwrap();
+    assert_eq!(puzzle.count(), 0);
+  }
+
+  #[test]
+  fn board_with_hole() {
+    //an 8x8 board missing its centre
+    let region: FreePointList = rectangle(8, 8)
+      .into_iter()
+      .filter(|pt| !(3..=4).contains(&pt.x) || !(3..=4).contains(&pt.y))
+      .collect();
+    let puzzle = Puzzle::new(&region, &pentominoes(), true).unwrap();
+    let solution = puzzle.solve().unwrap();
+    assert_eq!(solution.len(), 12);
+    assert!(is_tiling(&region, &solution));
+    //a 4x2 rectangle takes two squares
+    let mut square = Piece { omino: parse_ominos("##\n##").unwrap().remove(0), copies: Some(1) };
+    assert_eq!(Puzzle::new(&rectangle(4, 2), &[square.clone()], true).unwrap().solve(), None);
+    square.copies = None;
+    assert_eq!(Puzzle::new(&rectangle(4, 2), &[square.clone()], true).unwrap().count(), 1);
+    assert_eq!(Puzzle::new(&rectangle(3, 2), &[square.clone()], true).unwrap().count(), 0);
+    assert_eq!(Puzzle::new(&[], &[square], true).err(), Some(PuzzleError::EmptyRegion));
+  }
+
+  #[test]
+  fn puzzle_command() {
+    //a 4x2 rectangle drawn in a file is tiled by two squares from another
+    let dir = std::env::temp_dir().join(format!("polyominoes-puzzle-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (region, pieces) = (dir.join("region.txt"), dir.join("pieces.txt"));
+    std::fs::write(&region, "####\n####\n").unwrap();
+    std::fs::write(&pieces, "##\n##\n").unwrap();
+    let path = |path: &std::path::Path| path.to_str().unwrap().to_string();
+    let run = |args: &[String]| {
+      let mut out = vec![];
+      solve_ascii(args, &mut out).map(|()| String::from_utf8(out).unwrap())
+    };
+    assert_eq!(run(&[path(&region), path(&pieces)]), Ok("no tiling\n".to_string()));
+    let unlimited = [path(&region), path(&pieces), "--unlimited".to_string()];
+    assert_eq!(run(&unlimited), Ok("AABB\nAABB\nA: piece 1\nB: piece 1\n".to_string()));
+    assert_eq!(run(&[path(&region)]), Err(usage("puzzle needs a region file and a pieces file")));
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+}
@@ -0,0 +1,235 @@
+use std::{
+  io::{self, Write},
+  time::{Duration, Instant},
+};
+
+use crate::{oeis::A001168, omino::FreePointList, text::to_coords};
+
+/*
+Machine readable results of a search run, one record per omino size, so runs can be
+plotted and diffed across commits and machines. Records are written either as JSON
+Lines or as CSV. Failing ominos are written in the coordinate format from `text`, so
+they can be fed straight back into the `check` and `svg` commands.
+ */
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SizeReport {
+  pub size: u8,
+  pub count: u64,
+  //number of fixed ominos that failed
+  pub unsurroundable: u64,
+  //the failing ominos, one per rotation class
+  pub failures: Vec<FreePointList>,
+  pub seconds: f64,
+  pub method: &'static str,
+}
+
+impl SizeReport {
+  pub fn seconds_per_100k(&self) -> f64 {
+    if self.count == 0 {
+      return 0.0;
+    }
+    self.seconds / self.count as f64 * 100_000.0
+  }
+
+  pub fn to_json(&self) -> String {
+    let failures: Vec<_> = self.failures.iter().map(|f| json_string(&to_coords(f))).collect();
+    format!(
+      "{{\"size\":{},\"count\":{},\"unsurroundable\":{},\"failures\":[{}],\"seconds\":{},\
+      \"seconds_per_100k\":{},\"method\":{},\"version\":{}}}",
+      self.size,
+      self.count,
+      self.unsurroundable,
+      failures.join(","),
+      self.seconds,
+      self.seconds_per_100k(),
+      json_string(self.method),
+      json_string(env!("CARGO_PKG_VERSION"))
+    )
+  }
+
+  pub const CSV_HEADER: &'static str =
+    "size,count,unsurroundable,seconds,seconds_per_100k,method,version,failures";
+
+  pub fn to_csv(&self) -> String {
+    //failures are separated by ';' in one quoted field, since coordinates contain commas
+    let failures: Vec<_> = self.failures.iter().map(|f| to_coords(f)).collect();
+    format!(
+      "{},{},{},{},{},{},{},\"{}\"",
+      self.size,
+      self.count,
+      self.unsurroundable,
+      self.seconds,
+      self.seconds_per_100k(),
+      self.method,
+      env!("CARGO_PKG_VERSION"),
+      failures.join(";")
+    )
+  }
+}
+
+pub struct Progress {
+  size: u8,
+  //the number of fixed ominos of the size, if it is known
+  total: Option<u64>,
+  interval: Duration,
+  start: Instant,
+  //when the last line was printed, and how many ominos were done by then
+  last: (Instant, u64),
+}
+
+impl Progress {
+  //how many ominos go between checks of the clock
+  const CHECK_EVERY: u64 = 1 << 16;
+
+  pub fn new(size: u8, interval: Duration) -> Self {
+    let now = Instant::now();
+    let total = A001168.get(usize::from(size)).copied();
+    Progress { size, total, interval, start: now, last: (now, 0) }
+  }
+
+  pub fn tick(&mut self, done: u64, failures: u64) {
+    //prints a line to stderr if interval has passed since the last one
+    if done % Self::CHECK_EVERY != 0 {
+      return;
+    }
+    let now = Instant::now();
+    if now - self.last.0 < self.interval {
+      return;
+    }
+    eprintln!("{}", self.line(done, failures, now));
+    self.last = (now, done);
+  }
+
+  pub fn line(&self, done: u64, failures: u64, now: Instant) -> String {
+    /* ominos done, the seconds per 100k since the last line, the failures so far, and
+    the time left at the average rate so far if the total is known */
+    let (last_time, last_done) = self.last;
+    let recent = (now - last_time).as_secs_f64() / (done - last_done).max(1) as f64 * 100_000.0;
+    let mut line = format!("{}-ominoes: {}", self.size, done);
+    if let Some(total) = self.total {
+      let percent = 100.0 * done as f64 / total as f64;
+      line += &format!(" of {} ({:.2}%)", total, percent);
+    }
+    line += &format!(", {:.4} seconds per 100k, {} unsurroundable", recent, failures);
+    if let Some(total) = self.total.filter(|_| done > 0) {
+      let per_omino = (now - self.start).as_secs_f64() / done as f64;
+      let left = per_omino * total.saturating_sub(done) as f64;
+      line += &format!(", eta {}", format_duration(Duration::from_secs_f64(left)));
+    }
+    line
+  }
+}
+
+fn format_duration(duration: Duration) -> String {
+  let secs = duration.as_secs();
+  match secs {
+    0..=59 => format!("{}s", secs),
+    60..=3599 => format!("{}m{:02}s", secs / 60, secs % 60),
+    _ => format!("{}h{:02}m{:02}s", secs / 3600, secs / 60 % 60, secs % 60),
+  }
+}
+
+fn json_string(s: &str) -> String {
+  let mut out = String::with_capacity(s.len() + 2);
+  out.push('"');
+  for c in s.chars() {
+    match c {
+      '"' => out.push_str("\\\""),
+      '\\' => out.push_str("\\\\"),
+      c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+      c => out.push(c),
+    }
+  }
+  out.push('"');
+  out
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+  JsonLines,
+  Csv,
+}
+
+impl ReportFormat {
+  pub fn from_path(path: &str) -> Self {
+    //anything not named .csv is written as JSON Lines
+    if path.ends_with(".csv") {
+      ReportFormat::Csv
+    } else {
+      ReportFormat::JsonLines
+    }
+  }
+}
+
+pub struct ReportWriter<W: Write> {
+  inner: W,
+  format: ReportFormat,
+  wrote_header: bool,
+}
+
+impl<W: Write> ReportWriter<W> {
+  pub fn new(inner: W, format: ReportFormat) -> Self {
+    Self { inner, format, wrote_header: false }
+  }
+
+  pub fn write(&mut self, report: &SizeReport) -> io::Result<()> {
+    match self.format {
+      ReportFormat::JsonLines => writeln!(self.inner, "{}", report.to_json())?,
+      ReportFormat::Csv => {
+        if !self.wrote_header {
+          writeln!(self.inner, "{}", SizeReport::CSV_HEADER)?;
+          self.wrote_header = true;
+        }
+        writeln!(self.inner, "{}", report.to_csv())?
+      }
+    }
+    //flush every record, so a long run can be watched or cut short
+    self.inner.flush()
+  }
+}
+
+#[cfg(test)]
+pub mod test {
+  use super::*;
+  use crate::text::parse_coords;
+
+  #[test]
+  fn report_formats() {
+    let report = SizeReport {
+      size: 3,
+      count: 4,
+      unsurroundable: 2,
+      failures: vec![parse_coords("0,0 1,0 2,0").unwrap(), parse_coords("0,0 0,1").unwrap()],
+      seconds: 0.5,
+      method: "board",
+    };
+    let json = report.to_json();
+    assert!(json.starts_with(r#"{"size":3,"count":4,"unsurroundable":2,"#));
+    assert!(json.contains(r#""failures":["0,0 1,0 2,0","0,0 0,1"],"seconds":0.5,"#));
+    assert!(json.contains(r#""seconds_per_100k":12500,"method":"board","#));
+
+    let mut writer = ReportWriter::new(vec![], ReportFormat::Csv);
+    writer.write(&report).unwrap();
+    writer.write(&report).unwrap();
+    let csv = String::from_utf8(writer.inner).unwrap();
+    let lines: Vec<_> = csv.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0], SizeReport::CSV_HEADER);
+    assert!(lines[1].starts_with("3,4,2,0.5,12500,board,"));
+    assert!(lines[1].ends_with(r#","0,0 1,0 2,0;0,0 0,1""#));
+  }
+
+  #[test]
+  fn progress_lines() {
+    let progress = Progress::new(19, Duration::from_secs(10));
+    let line = progress.line(594_073_867, 2, progress.start + Duration::from_secs(60));
+    //a tenth of the way through in a minute leaves 9 minutes
+    assert!(line.starts_with("19-ominoes: 594073867 of 5940738676 (10.00%), 0.0101 seconds"));
+    assert!(line.ends_with(", 2 unsurroundable, eta 9m00s"), "{}", line);
+    let progress = Progress::new(40, Duration::from_secs(10));
+    let line = progress.line(100_000, 0, progress.start + Duration::from_secs(1));
+    assert_eq!(line, "40-ominoes: 100000, 1.0000 seconds per 100k, 0 unsurroundable");
+    assert_eq!(format_duration(Duration::from_secs(3 * 3600 + 62)), "3h01m02s");
+  }
+}
@@ -0,0 +1,230 @@
+use std::io::Write;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rustc_hash::FxHashSet as HashSet;
+
+use crate::{
+  board::has_arrangement_board,
+  command::{failed, usage, CommandError},
+  omino::{is_connected, normalize_omino, FreePoint, FreePointList},
+  stats::symmetry_mask,
+  text::to_ascii,
+};
+
+/*
+Samples uniformly random ominos of a given size, for sizes far too large to enumerate.
+
+The sampler is a Metropolis-Hastings Markov chain over fixed ominos. A step picks a
+cell c of the omino, another cell d and a direction, and proposes moving c to the
+square s next to d in that direction. If the rest R of the omino is r cells,
+this proposes s with probability k_R(s) / 4r, where k_R(x) counts the cells of R next
+to x, and the reverse move with probability k_R(c) / 4r, so accepting with probability
+min(1, k_R(c) / k_R(s)) makes the uniform distribution stationary. Any omino can be
+turned into a straight line by such moves, so the chain is irreducible, and rejected
+moves make it aperiodic, so it converges to the uniform distribution.
+
+Successive states are correlated, so samples are taken spacing steps apart after a
+burn in. Free ominos are sampled by rejection: an omino with |Sym| symmetries among
+the 8 rotations and reflections is one of 8 / |Sym| fixed ominos of its free class, so
+keeping a fixed sample with probability |Sym| / 8 leaves every free class equally
+likely.
+ */
+
+pub struct OminoSampler<R> {
+  rng: R,
+  omino: FreePointList,
+  cells: HashSet<FreePoint>,
+  spacing: u64,
+  free: bool,
+}
+
+impl<R: Rng> OminoSampler<R> {
+  pub fn new(size: u8, free: bool, spacing: u64, burn_in: u64, rng: R) -> Self {
+    //starts from a straight line, and runs burn_in steps before the first sample
+    assert!(size > 0);
+    let omino: FreePointList = (0..size as i8).map(|x| FreePoint { x, y: 0 }).collect();
+    let cells = omino.iter().copied().collect();
+    let mut sampler = OminoSampler { rng, omino, cells, spacing, free };
+    for _ in 0..burn_in {
+      sampler.step();
+    }
+    sampler
+  }
+
+  fn neighbors_in_rest(&self, pt: FreePoint, removed: FreePoint) -> usize {
+    pt.get_neighbors().iter().filter(|&&n| n != removed && self.cells.contains(&n)).count()
+  }
+
+  pub fn step(&mut self) -> bool {
+    //one step of the chain, returning whether the omino changed
+    let size = self.omino.len();
+    if size == 1 {
+      return false;
+    }
+    let c_index = self.rng.gen_range(0..size);
+    let d_index = (c_index + self.rng.gen_range(1..size)) % size;
+    let (c, d) = (self.omino[c_index], self.omino[d_index]);
+    let s = d.get_neighbors()[self.rng.gen_range(0..4)];
+    if self.cells.contains(&s) {
+      return false;
+    }
+    let (from, to) = (self.neighbors_in_rest(c, c), self.neighbors_in_rest(s, c));
+    if from < to && self.rng.gen_range(0..to) >= from {
+      return false;
+    }
+    let mut moved = self.omino.clone();
+    moved[c_index] = s;
+    if !is_connected(&moved) {
+      return false;
+    }
+    //keep the coordinates small, as the omino would otherwise wander off
+    self.omino = normalize_omino(moved);
+    self.cells = self.omino.iter().copied().collect();
+    true
+  }
+
+  pub fn symmetries(omino: &FreePointList) -> usize {
+    //how many of the 8 rotations and reflections map the omino to itself
+    symmetry_mask(omino).count_ones() as usize
+  }
+}
+
+impl<R: Rng> Iterator for OminoSampler<R> {
+  type Item = FreePointList;
+
+  fn next(&mut self) -> Option<FreePointList> {
+    loop {
+      for _ in 0..self.spacing {
+        self.step();
+      }
+      if !self.free || self.rng.gen_range(0..8) < Self::symmetries(&self.omino) {
+        return Some(self.omino.clone());
+      }
+    }
+  }
+}
+
+pub fn wilson_interval(successes: u64, trials: u64, z: f64) -> (f64, f64) {
+  /* the Wilson score interval for a proportion, which unlike the normal approximation
+  behaves when the proportion is near 0, as the fraction of unsurroundable ominos is.
+  z is 1.96 for a 95% interval */
+  if trials == 0 {
+    return (0.0, 1.0);
+  }
+  let n = trials as f64;
+  let p = successes as f64 / n;
+  let denominator = 1.0 + z * z / n;
+  let centre = (p + z * z / (2.0 * n)) / denominator;
+  let half_width = z * (p * (1.0 - p) / n + z * z / (4.0 * n * n)).sqrt() / denominator;
+  ((centre - half_width).max(0.0), (centre + half_width).min(1.0))
+}
+
+pub fn run(args: &[String], out: &mut impl Write) -> Result<(), CommandError> {
+  /* tests K uniformly random n-ominoes, fixed or with --free free, and prints the
+  fraction that can't surround a hole with a 95% confidence interval */
+  let Some(size) = args.first().and_then(|n| n.parse::<u8>().ok()) else {
+    return Err(usage("sample needs a size"));
+  };
+  if !(1..=63).contains(&size) {
+    return Err(failed("can only sample sizes 1 to 63"));
+  }
+  let mut count: u64 = 1000;
+  let mut free = false;
+  let mut seed = None;
+  let mut spacing = None;
+  let mut burn_in = None;
+  let mut args = args[1..].iter();
+  while let Some(arg) = args.next() {
+    if arg == "--free" {
+      free = true;
+      continue;
+    }
+    let Some(value) = args.next().and_then(|n| n.parse::<u64>().ok()) else {
+      return Err(usage(format!("bad option {:?}", arg)));
+    };
+    match arg.as_str() {
+      "--count" => count = value,
+      "--seed" => seed = Some(value),
+      "--spacing" => spacing = Some(value),
+      "--burn-in" => burn_in = Some(value),
+      _ => return Err(usage(format!("bad option {:?}", arg))),
+    }
+  }
+  //the chain needs many moves of every cell to forget where it was
+  let n = u64::from(size);
+  let spacing = spacing.unwrap_or(10 * n * n);
+  let burn_in = burn_in.unwrap_or(100 * n * n);
+  let seed = seed.unwrap_or_else(rand::random);
+  eprintln!("seed {}", seed);
+
+  let sampler = OminoSampler::new(size, free, spacing, burn_in, StdRng::seed_from_u64(seed));
+  let mut failures = vec![];
+  for omino in sampler.take(count as usize) {
+    if !has_arrangement_board(&omino) {
+      failures.push(omino);
+    }
+  }
+  let (low, high) = wilson_interval(failures.len() as u64, count, 1.96);
+  writeln!(
+    out,
+    "{} ominoes, samples: {} unsurroundable: {} estimate: {:.6} 95% interval: [{:.6}, {:.6}]",
+    size,
+    count,
+    failures.len(),
+    failures.len() as f64 / count.max(1) as f64,
+    low,
+    high
+  )?;
+  for failure in failures {
+    writeln!(out, "{}", to_ascii(&failure))?;
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+pub mod test {
+  use super::*;
+  use crate::assemble::{mirror_y_axis, rotate_omino};
+  use rand::{rngs::StdRng, SeedableRng};
+  use std::collections::HashMap;
+
+  #[test]
+  fn samples_are_roughly_uniform() {
+    //19 fixed tetrominoes, each should turn up about 1 time in 19
+    let sampler = OminoSampler::new(4, false, 20, 100, StdRng::seed_from_u64(1));
+    let mut counts: HashMap<FreePointList, usize> = HashMap::new();
+    for omino in sampler.take(19_000) {
+      assert_eq!(omino.len(), 4);
+      assert!(is_connected(&omino));
+      *counts.entry(omino).or_default() += 1;
+    }
+    assert_eq!(counts.len(), 19);
+    assert!(counts.values().all(|&count| (800..1200).contains(&count)), "{:?}", counts);
+
+    //5 free tetrominoes
+    let sampler = OminoSampler::new(4, true, 20, 100, StdRng::seed_from_u64(2));
+    let mut counts: HashMap<FreePointList, usize> = HashMap::new();
+    for omino in sampler.take(5_000) {
+      let canonical = (0..4)
+        .flat_map(|amt| {
+          let rotated = rotate_omino(&omino, amt);
+          let mirrored = rotated.iter().map(|&pt| mirror_y_axis(pt)).collect();
+          [normalize_omino(rotated), normalize_omino(mirrored)]
+        })
+        .min()
+        .unwrap();
+      *counts.entry(canonical).or_default() += 1;
+    }
+    assert_eq!(counts.len(), 5);
+    assert!(counts.values().all(|&count| (800..1200).contains(&count)), "{:?}", counts);
+  }
+
+  #[test]
+  fn wilson() {
+    let (low, high) = wilson_interval(0, 100, 1.96);
+    assert_eq!(low, 0.0);
+    assert!(high > 0.03 && high < 0.04);
+    let (low, high) = wilson_interval(50, 100, 1.96);
+    assert!((low - 0.404).abs() < 0.001 && (high - 0.596).abs() < 0.001);
+  }
+}
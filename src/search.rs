@@ -0,0 +1,201 @@
+use std::{
+  fs::File,
+  io::{BufWriter, Write},
+  path::Path,
+  time::{Duration, SystemTime},
+};
+
+use crate::{
+  assemble::rotational_deduplicate,
+  board::has_arrangement_board,
+  command::{failed, read_ominos, usage, CommandError},
+  db::ResultDb,
+  omino::{enumerate_polyominos, FreePointList, MAX_ENUMERABLE_SIZE},
+  report::{Progress, ReportFormat, ReportWriter, SizeReport},
+  stats::SizeStats,
+  text::to_ascii,
+};
+
+/*
+The search loop behind the `search` command: every fixed omino of a size is asked
+whether it can surround the hole, and the results go to a `report::SizeReport`. The
+`check` command asks the same of ominos read from a file.
+ */
+
+pub fn search_size(
+  size: u8,
+  mut stats: Option<&mut SizeStats>,
+  mut db: Option<&mut ResultDb>,
+  mut progress: Option<&mut Progress>,
+) -> SizeReport {
+  /* tests every fixed omino of the given size, collecting the ones that can't surround a
+  hole, and adding every omino to stats if given. With a db, shapes it has decided are
+  looked up rather than searched, and new ones are added to it. With progress, a line
+  is printed to stderr now and then during the size */
+  let start = SystemTime::now();
+  let mut untranslateable_ominos = vec![];
+  let mut num_ominos = 0;
+  enumerate_polyominos(size, |pts| {
+    num_ominos += 1;
+    if let Some(progress) = progress.as_mut() {
+      progress.tick(num_ominos, untranslateable_ominos.len() as u64);
+    }
+    let fpl: FreePointList = pts.iter().map(|&pt| pt.into()).collect();
+    if let Some(stats) = stats.as_mut() {
+      stats.add(&fpl);
+    }
+    let surroundable = match db.as_mut() {
+      Some(db) => db.surroundable(&fpl),
+      None => has_arrangement_board(&fpl),
+    };
+    if !surroundable {
+      untranslateable_ominos.push(fpl);
+    }
+  });
+  let end = SystemTime::now();
+  SizeReport {
+    size,
+    count: num_ominos,
+    unsurroundable: untranslateable_ominos.len() as u64,
+    failures: rotational_deduplicate(&untranslateable_ominos),
+    seconds: end.duration_since(start).unwrap().as_secs_f64(),
+    method: "board",
+  }
+}
+
+pub fn run(args: &[String], out: &mut impl Write) -> Result<(), CommandError> {
+  /* the main loop: for each size, tests every fixed omino of that size and prints
+  the ones that can't surround a hole. --report writes a record per size as JSON
+  Lines, or as CSV if the path ends in .csv. --stats prints a table of statistics for
+  each size. --db skips the shapes already decided in the result database at the path,
+  and adds the rest to it. A progress line goes to stderr every --progress seconds
+  while a size runs, or never with --progress 0. Sizes go up to MAX_ENUMERABLE_SIZE,
+  the default --to */
+  let mut from = 1;
+  let mut to = MAX_ENUMERABLE_SIZE;
+  let mut report = None;
+  let mut stats = false;
+  let mut db = None;
+  let mut progress_secs = 10;
+  let mut args = args.iter();
+  while let Some(arg) = args.next() {
+    let mut size = || match args.next().and_then(|n| n.parse().ok()) {
+      Some(n) if n <= MAX_ENUMERABLE_SIZE => Ok(n),
+      Some(_) => Err(usage(format!("can only search up to size {}", MAX_ENUMERABLE_SIZE))),
+      None => Err(usage(format!("{} needs a size", arg))),
+    };
+    match arg.as_str() {
+      "--stats" => stats = true,
+      "--from" => from = size()?,
+      "--to" => to = size()?,
+      "--progress" => match args.next().and_then(|n| n.parse().ok()) {
+        Some(secs) => progress_secs = secs,
+        None => return Err(usage("--progress needs a number of seconds")),
+      },
+      "--report" => match args.next() {
+        Some(path) => {
+          let file =
+            File::create(path).map_err(|e| failed(format!("could not create {}: {}", path, e)))?;
+          report = Some(ReportWriter::new(BufWriter::new(file), ReportFormat::from_path(path)));
+        }
+        None => return Err(usage("--report needs a file")),
+      },
+      "--db" => match args.next() {
+        Some(path) => match ResultDb::open(Path::new(path)) {
+          Ok(opened) => db = Some(opened),
+          Err(e) => return Err(failed(format!("could not open {}: {}", path, e))),
+        },
+        None => return Err(usage("--db needs a file")),
+      },
+      _ => return Err(usage(format!("unknown search option {:?}", arg))),
+    }
+  }
+
+  let mut previous_stats = None;
+  for i in from..=to {
+    let mut size_stats = stats.then(|| SizeStats::new(i));
+    let mut progress =
+      (progress_secs > 0).then(|| Progress::new(i, Duration::from_secs(progress_secs)));
+    let size_report = search_size(i, size_stats.as_mut(), db.as_mut(), progress.as_mut());
+    writeln!(
+      out,
+      "{} ominoes, count: {} untranslateable: {}, took {} seconds per 100k ominos",
+      i,
+      size_report.count,
+      size_report.unsurroundable,
+      size_report.seconds_per_100k()
+    )?;
+    for failure in &size_report.failures {
+      writeln!(out, "{}", to_ascii(failure))?;
+    }
+    if let Some(db) = db.as_mut() {
+      db.flush().map_err(|e| failed(format!("could not write result database: {}", e)))?;
+      writeln!(out, "{} shapes looked up, {} decided and stored", db.hits, db.decided)?;
+      (db.hits, db.decided) = (0, 0);
+    }
+    if let Some(size_stats) = size_stats {
+      write!(out, "{}", size_stats.table(previous_stats.as_ref()))?;
+      previous_stats = Some(size_stats);
+    }
+    if let Some(report) = report.as_mut() {
+      report.write(&size_report).map_err(|e| failed(format!("could not write report: {}", e)))?;
+    }
+  }
+  Ok(())
+}
+
+pub fn check_file(args: &[String], out: &mut impl Write) -> Result<(), CommandError> {
+  /* reports whether each omino read from a file can surround a hole. with
+  --unsurroundable only the ominos that can't are printed, followed by a count */
+  let mut only_failures = false;
+  let mut path = None;
+  for arg in args {
+    match arg.as_str() {
+      "--unsurroundable" => only_failures = true,
+      _ => path = Some(arg.as_str()),
+    }
+  }
+  let mut count = 0;
+  let mut failures = 0;
+  for omino in read_ominos(path)? {
+    let omino = omino?;
+    count += 1;
+    let surroundable = has_arrangement_board(&omino);
+    if !surroundable {
+      failures += 1;
+    }
+    if !only_failures {
+      let verdict = if surroundable { "surroundable" } else { "unsurroundable" };
+      writeln!(out, "{}{}\n", to_ascii(&omino), verdict)?;
+    } else if !surroundable {
+      writeln!(out, "{}", to_ascii(&omino))?;
+    }
+  }
+  if only_failures {
+    eprintln!("{} of {} ominos are unsurroundable", failures, count);
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+pub mod test {
+  use super::*;
+
+  #[test]
+  fn search_command() {
+    let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+    let mut out = vec![];
+    run(&args(&["--from", "3", "--to", "4", "--progress", "0"]), &mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.starts_with("3 ominoes, count: 6 untranslateable: 0, took "), "{}", out);
+    assert!(out.contains("\n4 ominoes, count: 19 untranslateable: 0, took "), "{}", out);
+    assert_eq!(
+      run(&args(&["--to", "99"]), &mut vec![]),
+      Err(usage(format!("can only search up to size {}", MAX_ENUMERABLE_SIZE)))
+    );
+    assert_eq!(
+      run(&args(&["--fast"]), &mut vec![]),
+      Err(usage("unknown search option \"--fast\""))
+    );
+  }
+}
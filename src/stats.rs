@@ -0,0 +1,231 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use itertools::Itertools;
+
+use crate::{
+  assemble::{iter_perimeter, mirror_y_axis, rotate_omino},
+  omino::{normalize_omino, FreePoint, FreePointList},
+};
+
+/*
+Statistics of the fixed ominos of one size, gathered one omino at a time during
+enumeration: how long their perimeters are, the shapes of their bounding boxes, which
+symmetries they have and how many holes they enclose.
+
+The symmetries of an omino are a subgroup of the 8 rotations and reflections of the
+square, and there are 8 kinds of subgroup, the symmetry classes. An omino whose group
+has k elements is one of 8 / k fixed ominos in its free class, so the free ominos in a
+class are its fixed ones times k / 8.
+ */
+
+pub const SYMMETRY_CLASSES: [&str; 8] = [
+  "none",
+  "rotation 180",
+  "rotation 90",
+  "axis mirror",
+  "diagonal mirror",
+  "both axes",
+  "both diagonals",
+  "all",
+];
+
+pub fn symmetry_mask(omino: &FreePointList) -> u8 {
+  /* which of the 8 rotations and reflections map the omino to itself, bit i for i cw
+  turns and bit 4 + i for mirroring in the y axis then i cw turns */
+  let normalized = normalize_omino(omino.clone());
+  let mirrored: FreePointList = omino.iter().map(|&pt| mirror_y_axis(pt)).collect();
+  let mut mask = 0;
+  for amt in 0..4 {
+    if normalize_omino(rotate_omino(omino, amt)) == normalized {
+      mask |= 1 << amt;
+    }
+    if normalize_omino(rotate_omino(&mirrored, amt)) == normalized {
+      mask |= 1 << (4 + amt);
+    }
+  }
+  mask
+}
+
+pub fn symmetry_class(mask: u8) -> usize {
+  //an index into SYMMETRY_CLASSES
+  const AXES: u8 = 0b0101_0000;
+  match mask.count_ones() {
+    1 => 0,
+    2 if mask & 0b0100 != 0 => 1,
+    2 if mask & AXES != 0 => 3,
+    2 => 4,
+    4 if mask & 0b1111 == 0b1111 => 2,
+    4 if mask & AXES == AXES => 5,
+    4 => 6,
+    _ => 7,
+  }
+}
+
+pub fn count_holes(omino: &[FreePoint]) -> usize {
+  //the number of connected regions of empty squares the omino encloses
+  let min_x = omino.iter().map(|pt| pt.x).min().unwrap() - 1;
+  let min_y = omino.iter().map(|pt| pt.y).min().unwrap() - 1;
+  let width = (omino.iter().map(|pt| pt.x).max().unwrap() - min_x + 2) as usize;
+  let height = (omino.iter().map(|pt| pt.y).max().unwrap() - min_y + 2) as usize;
+  //a margin of empty squares around the bounding box, which are all outside
+  let mut filled = vec![false; width * height];
+  for pt in omino {
+    filled[(pt.y - min_y) as usize * width + (pt.x - min_x) as usize] = true;
+  }
+  let fill = |start: usize, filled: &mut [bool]| {
+    let mut todo = vec![start];
+    filled[start] = true;
+    while let Some(i) = todo.pop() {
+      let (x, y) = (i % width, i / width);
+      let neighbors = [
+        (x > 0).then(|| i - 1),
+        (x + 1 < width).then_some(i + 1),
+        (y > 0).then(|| i - width),
+        (y + 1 < height).then_some(i + width),
+      ];
+      for j in neighbors.into_iter().flatten() {
+        if !filled[j] {
+          filled[j] = true;
+          todo.push(j);
+        }
+      }
+    }
+  };
+  fill(0, &mut filled);
+  let mut holes = 0;
+  for i in 0..filled.len() {
+    if !filled[i] {
+      fill(i, &mut filled);
+      holes += 1;
+    }
+  }
+  holes
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SizeStats {
+  pub size: u8,
+  pub count: u64,
+  //fixed ominos by number of perimeter edges
+  pub perimeters: BTreeMap<usize, u64>,
+  //fixed ominos by bounding box (width, height)
+  pub bounding_boxes: BTreeMap<(usize, usize), u64>,
+  //fixed ominos in each of SYMMETRY_CLASSES
+  pub symmetry_classes: [u64; 8],
+  //fixed ominos by number of holes
+  pub holes: BTreeMap<usize, u64>,
+}
+
+impl SizeStats {
+  pub fn new(size: u8) -> Self {
+    SizeStats { size, ..Default::default() }
+  }
+
+  pub fn add(&mut self, omino: &FreePointList) {
+    self.count += 1;
+    *self.perimeters.entry(iter_perimeter(omino).len()).or_default() += 1;
+    let extent = |coord: fn(&FreePoint) -> i8| {
+      let (min, max) = omino.iter().map(coord).minmax().into_option().unwrap();
+      (max - min) as usize + 1
+    };
+    let bounding_box = (extent(|pt| pt.x), extent(|pt| pt.y));
+    *self.bounding_boxes.entry(bounding_box).or_default() += 1;
+    self.symmetry_classes[symmetry_class(symmetry_mask(omino))] += 1;
+    *self.holes.entry(count_holes(omino)).or_default() += 1;
+  }
+
+  pub fn free_in_class(&self, class: usize) -> u64 {
+    //every omino in a class has the same number of symmetries
+    const GROUP_SIZES: [u64; 8] = [1, 2, 4, 2, 2, 4, 4, 8];
+    self.symmetry_classes[class] * GROUP_SIZES[class] / 8
+  }
+
+  pub fn free_count(&self) -> u64 {
+    (0..8).map(|class| self.free_in_class(class)).sum()
+  }
+
+  pub fn table(&self, previous: Option<&SizeStats>) -> String {
+    /* the statistics as a text table, with the growth ratio a(n) / a(n - 1) of fixed
+    counts if the previous size is given */
+    let mut out = String::new();
+    let percent = |n: u64| 100.0 * n as f64 / self.count.max(1) as f64;
+    writeln!(out, "{}-omino statistics", self.size).unwrap();
+    writeln!(out, "  fixed {:>14}", self.count).unwrap();
+    writeln!(out, "  free  {:>14}", self.free_count()).unwrap();
+    if let Some(previous) = previous.filter(|previous| previous.count > 0) {
+      let ratio = self.count as f64 / previous.count as f64;
+      writeln!(out, "  a({})/a({}) {:.6}", self.size, previous.size, ratio).unwrap();
+    }
+    writeln!(out, "  {:<16} {:>14} {:>8}", "perimeter", "fixed", "%").unwrap();
+    for (perimeter, &n) in &self.perimeters {
+      writeln!(out, "  {:<16} {:>14} {:>8.3}", perimeter, n, percent(n)).unwrap();
+    }
+    writeln!(out, "  {:<16} {:>14} {:>8}", "bounding box", "fixed", "%").unwrap();
+    for (&(width, height), &n) in &self.bounding_boxes {
+      let dims = format!("{}x{}", width, height);
+      writeln!(out, "  {:<16} {:>14} {:>8.3}", dims, n, percent(n)).unwrap();
+    }
+    writeln!(out, "  {:<16} {:>14} {:>8}", "symmetry", "fixed", "free").unwrap();
+    for (class, name) in SYMMETRY_CLASSES.iter().enumerate() {
+      let fixed = self.symmetry_classes[class];
+      writeln!(out, "  {:<16} {:>14} {:>8}", name, fixed, self.free_in_class(class)).unwrap();
+    }
+    writeln!(out, "  {:<16} {:>14} {:>8}", "holes", "fixed", "%").unwrap();
+    for (holes, &n) in &self.holes {
+      writeln!(out, "  {:<16} {:>14} {:>8.3}", holes, n, percent(n)).unwrap();
+    }
+    out
+  }
+}
+
+#[cfg(test)]
+pub mod test {
+  use super::*;
+  use crate::{omino::enumerate_polyominos, text::parse_ascii};
+
+  fn stats(size: u8) -> SizeStats {
+    let mut stats = SizeStats::new(size);
+    enumerate_polyominos(size, |pts| {
+      stats.add(&pts.iter().map(|&pt| pt.into()).collect());
+    });
+    stats
+  }
+
+  #[test]
+  fn symmetry_classes() {
+    //free polyominoes, OEIS A000105, and those with no symmetry, A006749
+    let free = [1, 1, 2, 5, 12, 35, 108, 369];
+    let asymmetric = [0, 0, 0, 1, 5, 20, 84, 316];
+    for size in 1..=8 {
+      let stats = stats(size);
+      assert_eq!(stats.free_count(), free[usize::from(size) - 1], "size {}", size);
+      assert_eq!(stats.free_in_class(0), asymmetric[usize::from(size) - 1], "size {}", size);
+    }
+    let square = parse_ascii("##\n##").unwrap();
+    assert_eq!(SYMMETRY_CLASSES[symmetry_class(symmetry_mask(&square))], "all");
+    let s = parse_ascii(".##\n##.").unwrap();
+    assert_eq!(SYMMETRY_CLASSES[symmetry_class(symmetry_mask(&s))], "rotation 180");
+    let t = parse_ascii("###\n.#.").unwrap();
+    assert_eq!(SYMMETRY_CLASSES[symmetry_class(symmetry_mask(&t))], "axis mirror");
+    let v = parse_ascii("#..\n#..\n###").unwrap();
+    assert_eq!(SYMMETRY_CLASSES[symmetry_class(symmetry_mask(&v))], "diagonal mirror");
+  }
+
+  #[test]
+  fn perimeters_boxes_and_holes() {
+    let stats = stats(7);
+    //the only heptomino with a hole is a ring missing a corner, in 4 orientations
+    assert_eq!(stats.holes.get(&1), Some(&4));
+    assert_eq!(stats.holes.get(&0), Some(&(760 - 4)));
+    //a tree of n squares has perimeter 2n + 2, and the most compact heptomino 12
+    assert_eq!(*stats.perimeters.keys().max().unwrap(), 16);
+    assert_eq!(*stats.perimeters.keys().min().unwrap(), 12);
+    assert_eq!(stats.bounding_boxes.get(&(7, 1)), Some(&1));
+    assert_eq!(stats.bounding_boxes.values().sum::<u64>(), 760);
+    let table = stats.table(None);
+    assert!(table.contains("fixed            760"));
+
+    let ring = parse_ascii("#####\n#.#.#\n#####").unwrap();
+    assert_eq!(count_holes(&ring), 2);
+  }
+}
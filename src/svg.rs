@@ -0,0 +1,184 @@
+use std::{fmt::Write, io};
+
+use crate::{
+  board::arrangement_board,
+  command::{read_ominos, usage, CommandError},
+  omino::{FreePoint, FreePointList},
+  text::to_ascii,
+};
+
+/*
+Writes ominos as SVG, either a single omino, an arrangement of copies around the
+hole (0,0), or a grid of many of either so a whole run's results fit in one picture.
+The y axis points up like everywhere else in the crate, so pictures match the
+text format in `text`.
+ */
+
+const CELL: i32 = 20;
+const MARGIN: i32 = 10;
+const HOLE_COLOUR: &str = "#d62728";
+const COLOURS: [&str; 8] =
+  ["#4e79a7", "#f28e2b", "#59a14f", "#b07aa1", "#edc948", "#76b7b2", "#ff9da7", "#9c755f"];
+
+struct Panel<'a> {
+  pieces: &'a [FreePointList],
+  hole: Option<FreePoint>,
+}
+
+struct Bounds {
+  min_x: i32,
+  max_x: i32,
+  min_y: i32,
+  max_y: i32,
+}
+
+impl Panel<'_> {
+  fn bounds(&self) -> Bounds {
+    let pts = self.pieces.iter().flatten().chain(self.hole.iter());
+    let mut bounds = Bounds { min_x: i32::MAX, max_x: i32::MIN, min_y: i32::MAX, max_y: i32::MIN };
+    for pt in pts {
+      bounds.min_x = bounds.min_x.min(pt.x.into());
+      bounds.max_x = bounds.max_x.max(pt.x.into());
+      bounds.min_y = bounds.min_y.min(pt.y.into());
+      bounds.max_y = bounds.max_y.max(pt.y.into());
+    }
+    if bounds.min_x > bounds.max_x {
+      return Bounds { min_x: 0, max_x: -1, min_y: 0, max_y: -1 };
+    }
+    bounds
+  }
+
+  fn size_px(&self) -> (i32, i32) {
+    let b = self.bounds();
+    ((b.max_x - b.min_x + 1) * CELL, (b.max_y - b.min_y + 1) * CELL)
+  }
+
+  fn draw(&self, out: &mut String, left: i32, top: i32) {
+    let b = self.bounds();
+    let cell = |out: &mut String, pt: FreePoint, fill: &str| {
+      let px = left + (i32::from(pt.x) - b.min_x) * CELL;
+      let py = top + (b.max_y - i32::from(pt.y)) * CELL;
+      writeln!(
+        out,
+        r##"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" stroke="#333" stroke-width="1"/>"##,
+        px, py, CELL, CELL, fill
+      )
+      .unwrap();
+    };
+    for (i, piece) in self.pieces.iter().enumerate() {
+      for &pt in piece {
+        cell(out, pt, COLOURS[i % COLOURS.len()]);
+      }
+    }
+    if let Some(hole) = self.hole {
+      cell(out, hole, HOLE_COLOUR);
+    }
+  }
+}
+
+fn render(panels: &[Panel], columns: usize) -> String {
+  let columns = columns.max(1);
+  let sizes: Vec<_> = panels.iter().map(|p| p.size_px()).collect();
+  let col_width = sizes.iter().map(|s| s.0).max().unwrap_or(0);
+  let row_height = sizes.iter().map(|s| s.1).max().unwrap_or(0);
+  let num_columns = columns.min(panels.len()).max(1) as i32;
+  let num_rows = panels.len().div_ceil(columns).max(1) as i32;
+  let width = num_columns * (col_width + MARGIN) + MARGIN;
+  let height = num_rows * (row_height + MARGIN) + MARGIN;
+
+  let mut out = String::new();
+  writeln!(
+    out,
+    r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+    width, height
+  )
+  .unwrap();
+  writeln!(out, r#"<rect width="{}" height="{}" fill="white"/>"#, width, height).unwrap();
+  for (i, panel) in panels.iter().enumerate() {
+    let (w, h) = sizes[i];
+    //centre each panel in its slot of the grid
+    let left = MARGIN + (i % columns) as i32 * (col_width + MARGIN) + (col_width - w) / 2;
+    let top = MARGIN + (i / columns) as i32 * (row_height + MARGIN) + (row_height - h) / 2;
+    panel.draw(&mut out, left, top);
+  }
+  out.push_str("</svg>\n");
+  out
+}
+
+pub fn omino_svg(omino: &FreePointList) -> String {
+  render(&[Panel { pieces: std::slice::from_ref(omino), hole: None }], 1)
+}
+
+pub fn arrangement_svg(copies: &[FreePointList]) -> String {
+  //copies are placed around the hole at (0,0), as returned by board::arrangement_board
+  render(&[Panel { pieces: copies, hole: Some(FreePoint { x: 0, y: 0 }) }], 1)
+}
+
+pub fn omino_grid_svg(ominos: &[FreePointList], columns: usize) -> String {
+  let panels: Vec<_> =
+    ominos.iter().map(|omino| Panel { pieces: std::slice::from_ref(omino), hole: None }).collect();
+  render(&panels, columns)
+}
+
+pub fn arrangement_grid_svg(arrangements: &[Vec<FreePointList>], columns: usize) -> String {
+  let panels: Vec<_> = arrangements
+    .iter()
+    .map(|copies| Panel { pieces: copies, hole: Some(FreePoint { x: 0, y: 0 }) })
+    .collect();
+  render(&panels, columns)
+}
+
+pub fn render_file(args: &[String], out: &mut impl io::Write) -> Result<(), CommandError> {
+  /* the svg command: draws the ominos read from a file (or stdin) as one svg, or with
+  --arrangement draws a surrounding arrangement for each one that has one */
+  let mut arrangement = false;
+  let mut columns = 8;
+  let mut path = None;
+  let mut args = args.iter();
+  while let Some(arg) = args.next() {
+    match arg.as_str() {
+      "--arrangement" => arrangement = true,
+      "--columns" => match args.next().and_then(|n| n.parse().ok()) {
+        Some(n) => columns = n,
+        None => return Err(usage("--columns needs a number")),
+      },
+      _ => path = Some(arg.as_str()),
+    }
+  }
+  let ominos = read_ominos(path)?.collect::<Result<Vec<_>, _>>()?;
+  let svg = if arrangement {
+    let mut arrangements = vec![];
+    for omino in ominos {
+      match arrangement_board(&omino) {
+        Some(copies) => arrangements.push(copies),
+        None => eprintln!("no arrangement for:\n{}", to_ascii(&omino)),
+      }
+    }
+    arrangement_grid_svg(&arrangements, columns)
+  } else {
+    omino_grid_svg(&ominos, columns)
+  };
+  out.write_all(svg.as_bytes())?;
+  Ok(())
+}
+
+#[cfg(test)]
+pub mod test {
+  use super::*;
+  use crate::text::parse_ascii;
+
+  #[test]
+  fn svg_has_a_rect_per_cell() {
+    let omino = parse_ascii(".##\n##.").unwrap();
+    let svg = omino_svg(&omino);
+    assert!(svg.starts_with("<svg"));
+    //one background rect plus one per cell
+    assert_eq!(svg.matches("<rect").count(), 5);
+    //3 cells wide and 2 tall, plus margins
+    assert!(svg.contains(r#"width="80" height="60""#));
+
+    let grid = omino_grid_svg(&[omino.clone(), omino.clone(), omino], 2);
+    assert_eq!(grid.matches("<rect").count(), 13);
+    assert!(grid.contains(r#"width="150" height="110""#));
+  }
+}
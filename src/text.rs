@@ -0,0 +1,267 @@
+use std::{collections::HashSet, fmt};
+
+use crate::omino::{is_connected, FreePoint, FreePointList};
+
+/*
+Two plain text formats for single ominos, so shapes can be written by hand, read
+from files or stdin, and printed back out.
+
+The picture format is rows of '#' (occupied) and '.' (empty), with the top row
+first, eg the S tetromino is
+  .##
+  ##.
+Reading a picture puts the bottom left corner of its bounding box at (0,0), and
+writing one only keeps the shape up to translation.
+
+The coordinate format is whitespace separated x,y pairs, eg "0,0 1,0 1,1 2,1".
+It keeps the exact points, so it round trips without normalizing.
+
+A file of several ominos is split into blocks by blank lines. A picture block is
+one omino, a coordinate block is one omino per line.
+ */
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ParseError {
+  Empty,
+  UnexpectedChar { line: usize, col: usize, ch: char },
+  BadCoordinate { line: usize, token: String },
+  DuplicatePoint(FreePoint),
+  TooLarge { line: usize },
+  NotConnected { line: usize },
+}
+
+impl fmt::Display for ParseError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      ParseError::Empty => write!(f, "no points in omino"),
+      ParseError::UnexpectedChar { line, col, ch } => {
+        write!(f, "line {}, column {}: unexpected character {:?}", line, col, ch)
+      }
+      ParseError::BadCoordinate { line, token } => {
+        write!(f, "line {}: {:?} is not an x,y coordinate", line, token)
+      }
+      ParseError::DuplicatePoint(FreePoint { x, y }) => {
+        write!(f, "point {},{} appears twice", x, y)
+      }
+      ParseError::TooLarge { line } => write!(f, "line {}: picture is too large", line),
+      ParseError::NotConnected { line } => write!(f, "line {}: omino is not connected", line),
+    }
+  }
+}
+
+impl std::error::Error for ParseError {}
+
+fn is_picture_line(line: &str) -> bool {
+  line.chars().all(|c| c == '#' || c == '.')
+}
+
+fn parse_picture_lines(lines: &[(usize, &str)]) -> Result<FreePointList, ParseError> {
+  let height = lines.len();
+  let mut out = FreePointList::new();
+  for (row, &(line_num, line)) in lines.iter().enumerate() {
+    let y: i8 =
+      (height - 1 - row).try_into().map_err(|_| ParseError::TooLarge { line: line_num })?;
+    for (col, ch) in line.chars().enumerate() {
+      match ch {
+        '#' => {
+          let x: i8 = col.try_into().map_err(|_| ParseError::TooLarge { line: line_num })?;
+          out.push(FreePoint { x, y });
+        }
+        '.' => (),
+        _ => return Err(ParseError::UnexpectedChar { line: line_num, col: col + 1, ch }),
+      }
+    }
+  }
+  if out.is_empty() {
+    return Err(ParseError::Empty);
+  }
+  //rows were read top down, so shift the bottom row of the bounding box to y = 0
+  let min_y = out.iter().map(|pt| pt.y).min().unwrap();
+  let min_x = out.iter().map(|pt| pt.x).min().unwrap();
+  for pt in out.iter_mut() {
+    *pt = FreePoint { x: pt.x - min_x, y: pt.y - min_y };
+  }
+  out.sort();
+  Ok(out)
+}
+
+fn parse_coord_line(line_num: usize, line: &str) -> Result<FreePointList, ParseError> {
+  let mut out = FreePointList::new();
+  let mut seen = HashSet::new();
+  for token in line.split_whitespace() {
+    let bad = || ParseError::BadCoordinate { line: line_num, token: token.to_owned() };
+    let (x, y) = token.split_once(',').ok_or_else(bad)?;
+    let x = x.parse().map_err(|_| bad())?;
+    let y = y.parse().map_err(|_| bad())?;
+    let pt = FreePoint { x, y };
+    if !seen.insert(pt) {
+      return Err(ParseError::DuplicatePoint(pt));
+    }
+    out.push(pt);
+  }
+  if out.is_empty() {
+    return Err(ParseError::Empty);
+  }
+  Ok(out)
+}
+
+fn numbered_lines(s: &str) -> impl Iterator<Item = (usize, &str)> {
+  s.lines().enumerate().map(|(i, line)| (i + 1, line.trim()))
+}
+
+pub fn parse_ascii(s: &str) -> Result<FreePointList, ParseError> {
+  let lines: Vec<_> = numbered_lines(s).filter(|(_, line)| !line.is_empty()).collect();
+  parse_picture_lines(&lines)
+}
+
+pub fn parse_coords(s: &str) -> Result<FreePointList, ParseError> {
+  let mut out = FreePointList::new();
+  let mut seen = HashSet::new();
+  for (line_num, line) in numbered_lines(s).filter(|(_, line)| !line.is_empty()) {
+    for pt in parse_coord_line(line_num, line)? {
+      if !seen.insert(pt) {
+        return Err(ParseError::DuplicatePoint(pt));
+      }
+      out.push(pt);
+    }
+  }
+  if out.is_empty() {
+    return Err(ParseError::Empty);
+  }
+  Ok(out)
+}
+
+pub fn parse_ominos(s: &str) -> Result<Vec<FreePointList>, ParseError> {
+  /* parses a file of ominos in either format, see the top of this module.
+  every omino must be connected */
+  let mut out = vec![];
+  let mut block: Vec<(usize, &str)> = vec![];
+  let mut flush = |block: &mut Vec<(usize, &str)>| -> Result<(), ParseError> {
+    if block.is_empty() {
+      return Ok(());
+    }
+    let parsed = if is_picture_line(block[0].1) {
+      vec![(block[0].0, parse_picture_lines(block)?)]
+    } else {
+      let mut parsed = vec![];
+      for &(line_num, line) in block.iter() {
+        parsed.push((line_num, parse_coord_line(line_num, line)?));
+      }
+      parsed
+    };
+    for (line, omino) in parsed {
+      if !is_connected(&omino) {
+        return Err(ParseError::NotConnected { line });
+      }
+      out.push(omino);
+    }
+    block.clear();
+    Ok(())
+  };
+  for (line_num, line) in numbered_lines(s) {
+    if line.is_empty() {
+      flush(&mut block)?;
+    } else {
+      block.push((line_num, line));
+    }
+  }
+  flush(&mut block)?;
+  Ok(out)
+}
+
+pub fn to_ascii(omino: &[FreePoint]) -> String {
+  if omino.is_empty() {
+    return String::new();
+  }
+  let min_x = omino.iter().map(|pt| pt.x).min().unwrap();
+  let max_x = omino.iter().map(|pt| pt.x).max().unwrap();
+  let min_y = omino.iter().map(|pt| pt.y).min().unwrap();
+  let max_y = omino.iter().map(|pt| pt.y).max().unwrap();
+  let pts: HashSet<FreePoint> = omino.iter().copied().collect();
+  let mut out = String::new();
+  for y in (min_y..=max_y).rev() {
+    for x in min_x..=max_x {
+      out.push(if pts.contains(&FreePoint { x, y }) { '#' } else { '.' });
+    }
+    out.push('\n');
+  }
+  out
+}
+
+pub fn to_labelled_ascii(ominos: &[FreePointList]) -> String {
+  /* a picture of several ominos together, each drawn with its own letter, in the
+  order A-Z then a-z, and '.' for empty squares */
+  let labels: Vec<char> = ('A'..='Z').chain('a'..='z').collect();
+  let pts: Vec<(FreePoint, char)> = ominos
+    .iter()
+    .enumerate()
+    .flat_map(|(i, omino)| omino.iter().map(move |&pt| (pt, i)))
+    .map(|(pt, i)| (pt, labels[i % labels.len()]))
+    .collect();
+  if pts.is_empty() {
+    return String::new();
+  }
+  let min_x = pts.iter().map(|(pt, _)| pt.x).min().unwrap();
+  let max_x = pts.iter().map(|(pt, _)| pt.x).max().unwrap();
+  let min_y = pts.iter().map(|(pt, _)| pt.y).min().unwrap();
+  let max_y = pts.iter().map(|(pt, _)| pt.y).max().unwrap();
+  let mut out = String::new();
+  for y in (min_y..=max_y).rev() {
+    for x in min_x..=max_x {
+      let label = pts.iter().find(|(pt, _)| *pt == FreePoint { x, y }).map(|&(_, c)| c);
+      out.push(label.unwrap_or('.'));
+    }
+    out.push('\n');
+  }
+  out
+}
+
+pub fn to_coords(omino: &[FreePoint]) -> String {
+  omino.iter().map(|FreePoint { x, y }| format!("{},{}", x, y)).collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+pub mod test {
+  use super::*;
+
+  fn s_tetromino() -> FreePointList {
+    [(0, 0), (1, 0), (1, 1), (2, 1)].into_iter().map(|(x, y)| FreePoint { x, y }).collect()
+  }
+
+  #[test]
+  fn ascii_round_trip() {
+    let picture = ".##\n##.\n";
+    let omino = parse_ascii(picture).unwrap();
+    assert_eq!(omino, s_tetromino());
+    assert_eq!(to_ascii(&omino), picture);
+  }
+
+  #[test]
+  fn labelled_picture() {
+    let ominos = [parse_coords("0,0 1,0").unwrap(), parse_coords("2,1 1,1 0,1").unwrap()];
+    assert_eq!(to_labelled_ascii(&ominos), "BBB\nAA.\n");
+  }
+
+  #[test]
+  fn coords_round_trip() {
+    let omino: FreePointList =
+      [(3, -2), (-1, 4), (0, 0)].into_iter().map(|(x, y)| FreePoint { x, y }).collect();
+    let text = to_coords(&omino);
+    assert_eq!(text, "3,-2 -1,4 0,0");
+    assert_eq!(parse_coords(&text).unwrap(), omino);
+  }
+
+  #[test]
+  fn parse_mixed_file() {
+    let file = "##\n#.\n\n0,0 1,0\n0,0 0,1 0,2\n";
+    let ominos = parse_ominos(file).unwrap();
+    assert_eq!(ominos.len(), 3);
+    assert_eq!(ominos[0].len(), 3);
+    assert_eq!(ominos[2], parse_coords("0,0 0,1 0,2").unwrap());
+    assert_eq!(parse_ominos("#.\n.#\n"), Err(ParseError::NotConnected { line: 1 }));
+    assert_eq!(
+      parse_ominos("##\n#x\n"),
+      Err(ParseError::UnexpectedChar { line: 2, col: 2, ch: 'x' })
+    );
+  }
+}